@@ -0,0 +1,410 @@
+use board::*;
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+const KING_DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 0),
+    (0, -1),
+    (-1, 0),
+    (1, 1),
+    (-1, 1),
+    (1, -1),
+    (-1, -1),
+];
+const KNIGHT_DIRECTIONS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (1, -2),
+    (2, -1),
+    (-1, -2),
+    (-2, -1),
+    (-1, 2),
+    (-2, 1),
+];
+
+/// Squares attacked by the piece standing on the given square. Sliding pieces stop on the first
+/// occupied square, which is included. Own pieces are not excluded.
+pub fn attacks(piece: Piece, position: Position, occupancy: Bitboard) -> Bitboard {
+    let square = position.index();
+    match piece.piece_type() {
+        PieceType::Pawn => TABLES.pawns[piece.player() as usize][square],
+        PieceType::Knight => TABLES.knights[square],
+        PieceType::King => TABLES.kings[square],
+        PieceType::Bishop => TABLES.bishops[square].attacks(&TABLES.sliders, occupancy),
+        PieceType::Rook => TABLES.rooks[square].attacks(&TABLES.sliders, occupancy),
+        PieceType::Queen => {
+            TABLES.bishops[square].attacks(&TABLES.sliders, occupancy)
+                | TABLES.rooks[square].attacks(&TABLES.sliders, occupancy)
+        }
+    }
+}
+
+lazy_static! {
+    static ref TABLES: Tables = Tables::new();
+}
+
+struct Tables {
+    pawns: [[Bitboard; 64]; 2],
+    knights: [Bitboard; 64],
+    kings: [Bitboard; 64],
+    bishops: [Magic; 64],
+    rooks: [Magic; 64],
+    sliders: Vec<Bitboard>,
+}
+
+impl Tables {
+    fn new() -> Self {
+        let mut sliders = vec![];
+        let bishops = magics(&BISHOP_DIRECTIONS, &BISHOP_MAGICS, &mut sliders);
+        let rooks = magics(&ROOK_DIRECTIONS, &ROOK_MAGICS, &mut sliders);
+        Self {
+            pawns: [
+                step_table(&[(-1, 1), (1, 1)]),
+                step_table(&[(-1, -1), (1, -1)]),
+            ],
+            knights: step_table(&KNIGHT_DIRECTIONS),
+            kings: step_table(&KING_DIRECTIONS),
+            bishops,
+            rooks,
+            sliders,
+        }
+    }
+}
+
+/// Sliding attacks of a square are stored at `offset` plus the index computed by multiplying the
+/// relevant occupancy by the magic number.
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: Bitboard) -> usize {
+        ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    fn attacks(&self, sliders: &[Bitboard], occupancy: Bitboard) -> Bitboard {
+        sliders[self.offset + self.index(occupancy)]
+    }
+}
+
+fn step_table(directions: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    for (square, attacks) in table.iter_mut().enumerate() {
+        *attacks = ray_attacks(square, 0, directions, 1);
+    }
+    table
+}
+
+fn magics(
+    directions: &[(i8, i8)],
+    numbers: &[u64; 64],
+    sliders: &mut Vec<Bitboard>,
+) -> [Magic; 64] {
+    let mut result = [Magic::default(); 64];
+    for (square, magic) in result.iter_mut().enumerate() {
+        let mask = relevant_occupancy(square, directions);
+        *magic = Magic {
+            mask,
+            magic: numbers[square],
+            shift: 64 - mask.count_ones(),
+            offset: sliders.len(),
+        };
+        sliders.resize(sliders.len() + (1 << mask.count_ones()), 0);
+        for occupancy in subsets(mask) {
+            let attacks = ray_attacks(square, occupancy, directions, u8::MAX);
+            let slot = &mut sliders[magic.offset + magic.index(occupancy)];
+            // A slider always attacks at least one square, so an empty slot is still unused
+            assert!(
+                *slot == 0 || *slot == attacks,
+                "Magic number of square {} maps different attacks to the same index",
+                square
+            );
+            *slot = attacks;
+        }
+    }
+    result
+}
+
+/// Squares whose occupancy changes the attacks of a slider: its rays without the last square.
+fn relevant_occupancy(square: usize, directions: &[(i8, i8)]) -> Bitboard {
+    directions
+        .iter()
+        .map(|direction| {
+            ray_attacks(square, 0, &[*direction], u8::MAX) & !ray_end(square, *direction)
+        })
+        .fold(0, |mask, ray| mask | ray)
+}
+
+fn ray_end(square: usize, direction: (i8, i8)) -> Bitboard {
+    let ray = ray_attacks(square, 0, &[direction], u8::MAX);
+    if ray == 0 {
+        0
+    } else if direction.1 > 0 || (direction.1 == 0 && direction.0 > 0) {
+        1 << (63 - ray.leading_zeros())
+    } else {
+        1 << ray.trailing_zeros()
+    }
+}
+
+fn subsets(mask: Bitboard) -> Vec<Bitboard> {
+    let mut result = vec![];
+    let mut subset: Bitboard = 0;
+    loop {
+        result.push(subset);
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            return result;
+        }
+    }
+}
+
+fn ray_attacks(square: usize, occupancy: Bitboard, directions: &[(i8, i8)], limit: u8) -> Bitboard {
+    let mut result = 0;
+    for (x, y) in directions {
+        let mut column = (square % 8) as i8;
+        let mut row = (square / 8) as i8;
+        for _ in 0..limit {
+            column += x;
+            row += y;
+            if !(0..8).contains(&column) || !(0..8).contains(&row) {
+                break;
+            }
+            let target: Bitboard = 1 << (row * 8 + column);
+            result |= target;
+            if occupancy & target != 0 {
+                break;
+            }
+        }
+    }
+    result
+}
+
+// Found by trying sparse random numbers until the attacks of all occupancies fit
+const BISHOP_MAGICS: [u64; 64] = [
+    0x8008_0298_0200_2200,
+    0x4291_0408_0880_2804,
+    0x0008_1800_4080_0300,
+    0x0008_8a02_02aa_1050,
+    0x0004_10a8_0000_0000,
+    0x0009_1008_0404_0009,
+    0x0801_1401_2108_0011,
+    0xa040_8084_0082_4000,
+    0x0000_08a0_0404_0048,
+    0x0600_2004_4080_8114,
+    0x2020_4104_0120_4403,
+    0x0004_0410_6200_c001,
+    0x0100_0110_4080_0026,
+    0x0008_0088_200a_0820,
+    0x0008_0048_0464_2080,
+    0x4000_0044_0298_1800,
+    0x0710_0022_2002_0088,
+    0x2010_8082_0202_0402,
+    0x8010_0808_4400_2820,
+    0x800c_0001_2402_8000,
+    0x0002_0004_2201_0040,
+    0x6438_4022_0042_2000,
+    0x0010_a100_4c0c_2000,
+    0x000a_00e1_0901_0190,
+    0x0802_2010_4004_14c0,
+    0x8428_0222_2024_0101,
+    0x0008_0880_0404_0010,
+    0x0008_0800_0022_0020,
+    0x0421_0100_0010_4000,
+    0x2191_0208_2500_a000,
+    0x0018_0080_4212_0150,
+    0x0210_8020_a09c_0402,
+    0x301c_2020_0089_0208,
+    0xa004_0220_0008_0100,
+    0x100c_0241_0088_1200,
+    0x8000_0808_0046_0a00,
+    0x1004_0108_0444_0040,
+    0x420c_9200_8004_1000,
+    0x0501_8c01_1444_0100,
+    0x0004_0100_308a_0080,
+    0x0020_8210_4280_1000,
+    0x0202_0261_2000_1c02,
+    0x0002_0010_4400_0800,
+    0x20aa_8442_0080_0801,
+    0x0000_0120_1100_1200,
+    0x0860_2090_0880_8042,
+    0x0008_1000_80a8_0200,
+    0x0808_0200_5042_0201,
+    0x0005_1c01_04c0_0000,
+    0x0000_8401_0882_0022,
+    0x000a_4618_4208_0004,
+    0x2400_4009_1488_0002,
+    0x0004_0040_1024_81b4,
+    0x2104_a142_0202_0060,
+    0x0004_0810_4102_0060,
+    0x00a0_8400_8200_5100,
+    0x0000_4122_1010_1482,
+    0x0108_5042_0804_2210,
+    0x0000_2004_4c04_0405,
+    0x4140_0502_0605_1401,
+    0x0122_0080_5182_0200,
+    0x0082_8004_2810_9100,
+    0x9104_0424_5444_0401,
+    0x141e_200c_0082_0848,
+];
+
+const ROOK_MAGICS: [u64; 64] = [
+    0x0280_0388_6040_0010,
+    0x0980_2000_4000_b080,
+    0x2100_1100_0840_2002,
+    0x0880_0800_8104_1000,
+    0x0200_0200_2004_1008,
+    0x2300_0400_0801_0012,
+    0x0c00_2830_0400_8201,
+    0x0180_0100_0040_7a80,
+    0x0168_8000_8040_0020,
+    0x0010_4000_4020_1000,
+    0x1001_0020_0100_1048,
+    0x1001_0024_0810_0100,
+    0x0801_0004_0801_0012,
+    0x4001_0002_0900_0400,
+    0x08a2_0004_c802_0001,
+    0x2002_8011_4500_2280,
+    0x0080_8600_2100_4200,
+    0x0010_00c0_0940_2002,
+    0x00b0_0020_0400_2800,
+    0x100a_8080_1002_0800,
+    0x8101_0100_0800_0410,
+    0x0244_0080_0200_0480,
+    0x0000_0400_1081_0208,
+    0x2000_0200_0044_8534,
+    0x4104_4004_8000_8033,
+    0x0000_8101_0020_4000,
+    0x0440_4309_0020_0010,
+    0x4600_2409_0010_0100,
+    0x0060_0800_8004_0080,
+    0x0001_0003_0008_0400,
+    0x0004_0844_0001_1002,
+    0x0023_0402_0000_8041,
+    0x0580_0500_4300_2080,
+    0x0400_8040_0280_2008,
+    0x0001_0020_0100_4010,
+    0x1000_2009_0100_1000,
+    0x4410_8008_0180_0c00,
+    0xa012_0038_0600_1004,
+    0x0020_1001_0400_8802,
+    0x0004_8084_0200_0041,
+    0x0010_4001_7089_8000,
+    0x0080_5000_2000_4004,
+    0x1040_4080_1202_0020,
+    0x8010_0400_0800_4040,
+    0x2001_0801_0011_0004,
+    0x0000_0200_0400_8080,
+    0x0021_0108_1004_0002,
+    0x0800_008c_4302_0024,
+    0x0000_8000_2100_5100,
+    0x0070_2010_4000_8080,
+    0x0000_d042_8200_6a00,
+    0x0010_0144_0008_0240,
+    0x0001_0801_1005_0100,
+    0x0012_0008_1024_0600,
+    0x0402_0008_0104_0200,
+    0x0281_0010_8a00_4100,
+    0x0050_8003_0010_2045,
+    0x8208_2100_4012_0882,
+    0x8010_6001_0118_3441,
+    0x020b_0009_1000_6045,
+    0x0241_0010_0248_0005,
+    0x0081_0004_0088_0241,
+    0x0000_0090_0802_4124,
+    0x0048_1229_8041_0402,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squares(positions: &[&str]) -> Bitboard {
+        positions
+            .iter()
+            .map(|position| Position::from(position).unwrap().bitboard())
+            .fold(0, |bitboard, square| bitboard | square)
+    }
+
+    #[test]
+    fn knight() {
+        let result = attacks(
+            Piece::new(PieceType::Knight, Player::White),
+            Position::from("a1").unwrap(),
+            0,
+        );
+        assert_eq!(result, squares(&["b3", "c2"]));
+    }
+
+    #[test]
+    fn pawn() {
+        let e4 = Position::from("e4").unwrap();
+        assert_eq!(
+            attacks(Piece::new(PieceType::Pawn, Player::White), e4, 0),
+            squares(&["d5", "f5"])
+        );
+        assert_eq!(
+            attacks(Piece::new(PieceType::Pawn, Player::Black), e4, 0),
+            squares(&["d3", "f3"])
+        );
+    }
+
+    #[test]
+    fn rook_blocked() {
+        // Given
+        let occupancy = squares(&["d6", "d2", "b4", "g4", "h8"]);
+
+        // When
+        let result = attacks(
+            Piece::new(PieceType::Rook, Player::Black),
+            Position::from("d4").unwrap(),
+            occupancy,
+        );
+
+        // Then
+        assert_eq!(
+            result,
+            squares(&["d5", "d6", "d3", "d2", "c4", "b4", "e4", "f4", "g4"])
+        );
+    }
+
+    #[test]
+    fn queen_on_empty_board() {
+        let result = attacks(
+            Piece::new(PieceType::Queen, Player::White),
+            Position::from("d4").unwrap(),
+            0,
+        );
+        assert_eq!(result.count_ones(), 27);
+    }
+
+    #[test]
+    fn magics_match_rays() {
+        let mut occupancy: u64 = 0x2545_f491_4f6c_dd1d;
+        for index in 0..64 {
+            for _ in 0..32 {
+                occupancy ^= occupancy << 13;
+                occupancy ^= occupancy >> 7;
+                occupancy ^= occupancy << 17;
+                let sparse = occupancy & (occupancy >> 3);
+                let position = Position::from_index(index);
+                assert_eq!(
+                    attacks(
+                        Piece::new(PieceType::Bishop, Player::White),
+                        position,
+                        sparse
+                    ),
+                    ray_attacks(index, sparse, &BISHOP_DIRECTIONS, u8::MAX)
+                );
+                assert_eq!(
+                    attacks(Piece::new(PieceType::Rook, Player::White), position, sparse),
+                    ray_attacks(index, sparse, &ROOK_DIRECTIONS, u8::MAX)
+                );
+            }
+        }
+    }
+}
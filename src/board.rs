@@ -1,7 +1,6 @@
 use std::fmt;
 use std::fmt::Display;
 
-use im::hashmap::HashMap;
 use regex::Regex;
 
 #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
@@ -14,14 +13,14 @@ pub enum PieceType {
     Pawn,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub struct Piece {
     piece_type: PieceType,
     player: Player,
 }
 
 impl Piece {
-    pub fn new(piece_type: PieceType, player: Player) -> Self {
+    pub const fn new(piece_type: PieceType, player: Player) -> Self {
         Self { piece_type, player }
     }
 
@@ -34,7 +33,7 @@ impl Piece {
     }
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub enum Player {
     White,
     Black,
@@ -72,7 +71,7 @@ impl Position {
     }
 
     pub fn from_chars(column: char, row: char) -> Option<Self> {
-        if column >= 'a' && column <= 'h' && row >= '1' && row <= '8' {
+        if ('a'..='h').contains(&column) && ('1'..='8').contains(&row) {
             Some(Self {
                 row: row as u8,
                 column: column as u8,
@@ -83,7 +82,7 @@ impl Position {
     }
 
     pub fn from_u8(column: u8, row: u8) -> Option<Self> {
-        if column >= b'a' && column <= b'h' && row >= b'1' && row <= b'8' {
+        if (b'a'..=b'h').contains(&column) && (b'1'..=b'8').contains(&row) {
             Some(Self { row, column })
         } else {
             None
@@ -97,6 +96,58 @@ impl Position {
     pub fn row(self) -> u8 {
         self.row
     }
+
+    /// Square number from 0 for a1 to 63 for h8, going through the first row first.
+    pub fn index(self) -> usize {
+        ((self.row - b'1') * 8 + (self.column - b'a')) as usize
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        POSITIONS[index]
+    }
+
+    pub fn bitboard(self) -> Bitboard {
+        1 << self.index()
+    }
+}
+
+const POSITIONS: [Position; 64] = {
+    let mut positions = [Position {
+        column: b'a',
+        row: b'1',
+    }; 64];
+    let mut index = 0;
+    while index < 64 {
+        positions[index] = Position {
+            column: b'a' + (index % 8) as u8,
+            row: b'1' + (index / 8) as u8,
+        };
+        index += 1;
+    }
+    positions
+};
+
+/// Set of squares, bit n standing for the square of index n.
+pub type Bitboard = u64;
+
+pub fn positions(bitboard: Bitboard) -> Positions {
+    Positions(bitboard)
+}
+
+pub struct Positions(Bitboard);
+
+impl Iterator for Positions {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Position> {
+        if self.0 == 0 {
+            None
+        } else {
+            let index = self.0.trailing_zeros() as usize;
+            self.0 &= self.0 - 1;
+            Some(POSITIONS[index])
+        }
+    }
 }
 
 impl Display for Position {
@@ -107,145 +158,39 @@ impl Display for Position {
 
 use self::PieceType::*;
 use self::Player::*;
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Board {
-    squares: HashMap<Position, Piece>,
+    pieces: [Bitboard; 6],
+    players: [Bitboard; 2],
+    squares: [Option<Piece>; 64],
 }
 
 impl Board {
     pub fn starting_position() -> Self {
         lazy_static! {
             static ref STARTING_POS: Board = {
-                let mut board_map = HashMap::new();
-                board_map.insert(
-                    Position::from("e1").unwrap(),
-                    Piece {
-                        piece_type: King,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("e8").unwrap(),
-                    Piece {
-                        piece_type: King,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("d1").unwrap(),
-                    Piece {
-                        piece_type: Queen,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("d8").unwrap(),
-                    Piece {
-                        piece_type: Queen,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("c1").unwrap(),
-                    Piece {
-                        piece_type: Bishop,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("c8").unwrap(),
-                    Piece {
-                        piece_type: Bishop,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("f1").unwrap(),
-                    Piece {
-                        piece_type: Bishop,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("f8").unwrap(),
-                    Piece {
-                        piece_type: Bishop,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("b1").unwrap(),
-                    Piece {
-                        piece_type: Knight,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("b8").unwrap(),
-                    Piece {
-                        piece_type: Knight,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("g1").unwrap(),
-                    Piece {
-                        piece_type: Knight,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("g8").unwrap(),
-                    Piece {
-                        piece_type: Knight,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("a1").unwrap(),
-                    Piece {
-                        piece_type: Rook,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("a8").unwrap(),
-                    Piece {
-                        piece_type: Rook,
-                        player: Black,
-                    },
-                );
-                board_map.insert(
-                    Position::from("h1").unwrap(),
-                    Piece {
-                        piece_type: Rook,
-                        player: White,
-                    },
-                );
-                board_map.insert(
-                    Position::from("h8").unwrap(),
-                    Piece {
-                        piece_type: Rook,
-                        player: Black,
-                    },
-                );
-                for c in "abcdefgh".chars() {
-                    board_map.insert(
-                        Position::from(&format!("{}2", c)).unwrap(),
-                        Piece {
-                            piece_type: Pawn,
-                            player: White,
-                        },
-                    );
-                    board_map.insert(
-                        Position::from(&format!("{}7", c)).unwrap(),
-                        Piece {
-                            piece_type: Pawn,
-                            player: Black,
-                        },
-                    );
+                let back_row = [Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook];
+                let mut board = Board::empty();
+                for (column, piece_type) in "abcdefgh".chars().zip(back_row.iter()) {
+                    board = board
+                        .put(
+                            Position::from_chars(column, '1').unwrap(),
+                            Piece::new(*piece_type, White),
+                        )
+                        .put(
+                            Position::from_chars(column, '8').unwrap(),
+                            Piece::new(*piece_type, Black),
+                        )
+                        .put(
+                            Position::from_chars(column, '2').unwrap(),
+                            Piece::new(Pawn, White),
+                        )
+                        .put(
+                            Position::from_chars(column, '7').unwrap(),
+                            Piece::new(Pawn, Black),
+                        );
                 }
-                Board { squares: board_map }
+                board
             };
         }
         STARTING_POS.clone()
@@ -253,33 +198,56 @@ impl Board {
 
     pub fn empty() -> Self {
         Self {
-            squares: HashMap::new(),
+            pieces: [0; 6],
+            players: [0; 2],
+            squares: [None; 64],
         }
     }
 
     pub fn get(&self, position: Position) -> Option<&Piece> {
-        self.squares.get(&position)
+        self.squares[position.index()].as_ref()
     }
 
     pub fn put(&self, position: Position, piece: Piece) -> Self {
-        Self {
-            squares: self.squares.update(position, piece),
-        }
+        let mut board = self.remove(position);
+        board.pieces[piece.piece_type as usize] |= position.bitboard();
+        board.players[piece.player as usize] |= position.bitboard();
+        board.squares[position.index()] = Some(piece);
+        board
     }
 
     pub fn remove(&self, position: Position) -> Self {
-        Self {
-            squares: self.squares.without(&position),
+        let mut board = self.clone();
+        if let Some(piece) = board.squares[position.index()].take() {
+            board.pieces[piece.piece_type as usize] &= !position.bitboard();
+            board.players[piece.player as usize] &= !position.bitboard();
         }
+        board
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&Position, &Piece)> {
-        self.squares.iter()
+        positions(self.occupancy()).map(move |position| {
+            let index = position.index();
+            (&POSITIONS[index], self.squares[index].as_ref().unwrap())
+        })
+    }
+
+    pub fn occupancy(&self) -> Bitboard {
+        self.players[0] | self.players[1]
+    }
+
+    pub fn player_occupancy(&self, player: Player) -> Bitboard {
+        self.players[player as usize]
+    }
+
+    pub fn pieces(&self, piece: Piece) -> Bitboard {
+        self.pieces[piece.piece_type as usize] & self.players[piece.player as usize]
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::positions;
     use super::Board;
     use super::Piece;
     use super::PieceType::*;
@@ -457,4 +425,60 @@ mod tests {
         // Then
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn position_index() {
+        for index in 0..64 {
+            assert_eq!(Position::from_index(index).index(), index);
+        }
+        assert_eq!(Position::from("a1").unwrap().index(), 0);
+        assert_eq!(Position::from("h1").unwrap().index(), 7);
+        assert_eq!(Position::from("h8").unwrap().index(), 63);
+    }
+
+    #[test]
+    fn put_replaces_piece() {
+        // Given
+        let board = Board::starting_position();
+        let e2 = Position::from("e2").unwrap();
+
+        // When
+        let result = board.put(e2, Piece::new(Queen, Black));
+
+        // Then
+        assert_eq!(result.get(e2), Some(&Piece::new(Queen, Black)));
+        assert_eq!(result.pieces(Piece::new(Pawn, White)).count_ones(), 7);
+        assert_eq!(result.player_occupancy(White).count_ones(), 15);
+        assert_eq!(result.player_occupancy(Black).count_ones(), 17);
+        assert_eq!(result.occupancy().count_ones(), 32);
+    }
+
+    #[test]
+    fn remove() {
+        // Given
+        let board = Board::starting_position();
+        let e2 = Position::from("e2").unwrap();
+
+        // When
+        let result = board.remove(e2);
+
+        // Then
+        assert_eq!(result.get(e2), None);
+        assert_eq!(result.occupancy() & e2.bitboard(), 0);
+        assert_eq!(result.iter().count(), 31);
+        assert_eq!(result.remove(e2), result);
+    }
+
+    #[test]
+    fn iterate_positions() {
+        // Given
+        let bitboard =
+            Position::from("c3").unwrap().bitboard() | Position::from("f7").unwrap().bitboard();
+
+        // When
+        let result: Vec<String> = positions(bitboard).map(|p| p.to_string()).collect();
+
+        // Then
+        assert_eq!(result, vec!["c3", "f7"]);
+    }
 }
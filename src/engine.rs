@@ -1,79 +1,1017 @@
-use im::Vector;
-use rayon::prelude::*;
-use std::cmp::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use attacks::attacks;
 use board::Piece;
 use board::PieceType;
 use board::Player;
 use game::Game;
 use game::Move;
+use game::OwnedMove;
+use im::Vector;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+mod config;
+mod limits;
+mod ordering;
+mod time;
+mod transposition;
+
+use self::ordering::{is_quiet, MoveOrdering};
 
-pub fn select_move(game: &Game, depth: i32) -> (Option<Move>, i32) {
-    alpha_beta(game, i32::MIN, i32::MAX, depth)
+pub use self::config::SearchConfig;
+pub use self::limits::SearchLimits;
+pub use self::time::TimeControl;
+pub use self::transposition::{Bound, Entry, TranspositionTable};
+
+/// Transposition table size in megabytes used by default.
+pub const DEFAULT_HASH_SIZE: usize = 16;
+/// Iterative deepening stops at this depth even if there is time left.
+const MAX_DEPTH: i32 = 64;
+/// Number of nodes searched between two checks of the clock.
+const NODES_BETWEEN_CLOCK_CHECKS: u64 = 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// Score of a checkmate on the board. A mate found `n` plies away is scored `MATE - n`.
+pub const MATE: i32 = 20000;
+/// Scores closer to `MATE` than this are mates.
+const MAX_MATE_PLY: i32 = 1000;
+/// Bound of the search window, larger than any score.
+const INFINITY: i32 = MATE + 1;
+/// Singular extensions are only tried from this depth.
+const SINGULAR_MIN_DEPTH: i32 = 4;
+/// The other moves must score below the transposition table score by this margin per ply of
+/// depth for the hash move to be singular.
+const SINGULAR_MARGIN: i32 = 25;
+/// Half width of the first aspiration window. It doubles after each failure.
+const ASPIRATION_WINDOW: i32 = 50;
+/// Aspiration windows wider than this are replaced by the full window.
+const MAX_ASPIRATION_WINDOW: i32 = 800;
+/// Depth reduction of the search after a null move, on top of the ply of the null move.
+const NULL_MOVE_REDUCTION: i32 = 2;
+/// Moves searched at full depth before the late move reductions start.
+const FULL_DEPTH_MOVES: usize = 3;
+/// Quiet moves are skipped at depth 1 and 2 when the position is this far below alpha.
+const FUTILITY_MARGINS: [i32; 3] = [0, 200, 500];
+/// The quiescence search replaces the search at depth 1 and 2 when the position is this far
+/// below alpha.
+const RAZORING_MARGINS: [i32; 3] = [0, 300, 600];
+/// Score of each square a piece other than a pawn attacks, and is not occupied by its own pieces.
+const MOBILITY_WEIGHT: i32 = 10;
+
+pub fn select_move(game: &Game, depth: i32) -> SearchResult<'_> {
+    Engine::new().select_move(game, depth)
 }
 
-fn alpha_beta(game: &Game, alpha: i32, beta: i32, depth: i32) -> (Option<Move>, i32) {
-    if depth <= 0 {
-        (None, score_game(game))
-    } else {
-        let candidates = game.list_moves();
-        if candidates.len() == 0 {
-            (None, score_game(game))
-        } else if game.turn() == Player::Black {
-            let mut beta = beta;
-            let mut m = None;
-            for candidate in candidates {
-                let (_, result) = alpha_beta(&candidate.new_game(), alpha, beta, depth - 1);
-                if result <= alpha {
-                    return (None, alpha);
+/// Outcome of a search: the best move, its score and the line the engine expects.
+#[derive(Clone, Debug)]
+pub struct SearchResult<'a> {
+    best_move: Option<Move<'a>>,
+    score: i32,
+    pv: Vec<OwnedMove>,
+}
+
+impl<'a> SearchResult<'a> {
+    fn new(game: &'a Game, score: i32, pv: Vec<OwnedMove>) -> Self {
+        Self {
+            best_move: find_move(game, pv.first().cloned()),
+            score,
+            pv,
+        }
+    }
+
+    pub fn best_move(&self) -> Option<Move<'a>> {
+        self.best_move
+    }
+
+    /// Score from the point of view of the side to move.
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn mate(&self) -> Option<i32> {
+        moves_to_mate(self.score)
+    }
+
+    /// Principal variation: the best move followed by the best replies found for both sides.
+    pub fn pv(&self) -> &[OwnedMove] {
+        &self.pv
+    }
+}
+
+type Progress = Box<dyn FnMut(&SearchInfo) + Send>;
+
+/// Search state kept from one move to the next.
+pub struct Engine {
+    transposition_table: Arc<TranspositionTable>,
+    ordering: MoveOrdering,
+    config: SearchConfig,
+    threads: usize,
+    /// Runs the helper threads, when there are several threads.
+    pool: Option<ThreadPool>,
+    root_depth: i32,
+    stats: SearchStats,
+    deadline: Option<Instant>,
+    node_limit: Option<u64>,
+    /// Shared with the helper threads, to stop them once the main thread is done, and with the
+    /// stop handles.
+    stop: Arc<AtomicBool>,
+    /// Whether the search may stop before the current depth is completed.
+    interruptible: bool,
+    stopped: bool,
+    /// Nodes of all the threads, counted by batches during the search.
+    shared_nodes: Arc<AtomicU64>,
+    progress: Option<Progress>,
+    start: Instant,
+    last_report: Instant,
+}
+
+/// Stops a search of an engine from another thread.
+#[derive(Clone, Debug)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    /// The search returns the result of the deepest completed depth, once the first depth is
+    /// completed. Stopping before the search starts stops it as soon as possible.
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Counters of the last search, summed over all the threads.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SearchStats {
+    nodes: u64,
+    quiescence_nodes: u64,
+    cutoffs: u64,
+    first_move_cutoffs: u64,
+    extensions: u64,
+    singular_extensions: u64,
+    seldepth: usize,
+}
+
+impl SearchStats {
+    /// Positions visited, including the ones of the quiescence search.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn quiescence_nodes(&self) -> u64 {
+        self.quiescence_nodes
+    }
+
+    /// Positions where a move was good enough to skip the remaining ones.
+    pub fn cutoffs(&self) -> u64 {
+        self.cutoffs
+    }
+
+    /// Cutoffs caused by the first move tried, which tells how good the move ordering is.
+    pub fn first_move_cutoffs(&self) -> u64 {
+        self.first_move_cutoffs
+    }
+
+    /// Moves searched one ply deeper, including the singular extensions.
+    pub fn extensions(&self) -> u64 {
+        self.extensions
+    }
+
+    pub fn singular_extensions(&self) -> u64 {
+        self.singular_extensions
+    }
+
+    /// Deepest ply reached, including the extensions and the quiescence search.
+    pub fn seldepth(&self) -> usize {
+        self.seldepth
+    }
+
+    fn add(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.quiescence_nodes += other.quiescence_nodes;
+        self.cutoffs += other.cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.extensions += other.extensions;
+        self.singular_extensions += other.singular_extensions;
+        self.seldepth = self.seldepth.max(other.seldepth);
+    }
+}
+
+/// Progress of a running search, as reported to the progress callback.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SearchInfo {
+    depth: i32,
+    seldepth: usize,
+    nodes: u64,
+    elapsed: Duration,
+    hashfull: u32,
+    score: Option<i32>,
+    pv: Vec<OwnedMove>,
+}
+
+impl SearchInfo {
+    /// Depth being searched, or completed when there is a score.
+    pub fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    pub fn seldepth(&self) -> usize {
+        self.seldepth
+    }
+
+    /// Nodes of all the threads.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn nps(&self) -> u64 {
+        (self.nodes as u128 * 1_000_000 / self.elapsed.as_micros().max(1)) as u64
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Used transposition table entries per thousand.
+    pub fn hashfull(&self) -> u32 {
+        self.hashfull
+    }
+
+    /// Score of the completed depth, from the point of view of the side to move.
+    pub fn score(&self) -> Option<i32> {
+        self.score
+    }
+
+    pub fn pv(&self) -> &[OwnedMove] {
+        &self.pv
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::with_hash_size(DEFAULT_HASH_SIZE)
+    }
+
+    pub fn with_hash_size(megabytes: usize) -> Self {
+        Self {
+            transposition_table: Arc::new(TranspositionTable::new(megabytes)),
+            ordering: MoveOrdering::new(),
+            config: SearchConfig::new(),
+            threads: 1,
+            pool: None,
+            root_depth: 0,
+            stats: SearchStats::default(),
+            deadline: None,
+            node_limit: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            interruptible: false,
+            stopped: false,
+            shared_nodes: Arc::new(AtomicU64::new(0)),
+            progress: None,
+            start: Instant::now(),
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Searcher of another thread, sharing the transposition table and the stop flag.
+    fn helper(&self) -> Self {
+        Self {
+            transposition_table: Arc::clone(&self.transposition_table),
+            ordering: self.ordering.clone(),
+            config: self.config,
+            threads: 1,
+            pool: None,
+            root_depth: 0,
+            stats: SearchStats::default(),
+            deadline: None,
+            node_limit: None,
+            stop: Arc::clone(&self.stop),
+            interruptible: true,
+            stopped: false,
+            shared_nodes: Arc::clone(&self.shared_nodes),
+            progress: None,
+            start: self.start,
+            last_report: self.last_report,
+        }
+    }
+
+    pub fn transposition_table(&self) -> &TranspositionTable {
+        &self.transposition_table
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    pub fn config(&self) -> SearchConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+    }
+
+    /// Called after each completed depth, and every second during a depth.
+    pub fn set_progress<F>(&mut self, progress: F)
+    where
+        F: FnMut(&SearchInfo) + Send + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+    }
+
+    /// Handle for the current or next search only: the handles given before no longer stop
+    /// anything, so that a late stop of a finished search cannot cut the next one short.
+    pub fn stop_handle(&mut self) -> StopHandle {
+        self.stop = Arc::new(AtomicBool::new(false));
+        StopHandle(Arc::clone(&self.stop))
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Lazy SMP: the other threads search the same position, and help the main thread through
+    /// the transposition table they share.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+        self.pool = if self.threads > 1 {
+            Some(
+                ThreadPoolBuilder::new()
+                    .num_threads(self.threads - 1)
+                    .build()
+                    .expect("Cannot start the search threads"),
+            )
+        } else {
+            None
+        };
+    }
+
+    /// Forgets what was learnt from the previous game.
+    pub fn new_game(&mut self) {
+        self.transposition_table.clear();
+        self.ordering.clear();
+    }
+
+    fn start_search(&mut self, limits: &SearchLimits) {
+        self.deadline = limits.movetime().map(|movetime| Instant::now() + movetime);
+        self.node_limit = limits.nodes();
+        self.interruptible = false;
+        self.stopped = false;
+        self.stats = SearchStats::default();
+        self.shared_nodes.store(0, Ordering::Relaxed);
+        self.start = Instant::now();
+        self.last_report = self.start;
+        self.ordering.age();
+    }
+
+    pub fn select_move<'a>(&mut self, game: &'a Game, depth: i32) -> SearchResult<'a> {
+        self.search(game, &SearchLimits::new().with_depth(depth))
+    }
+
+    pub fn select_move_in_time<'a>(
+        &mut self,
+        game: &'a Game,
+        budget: Duration,
+    ) -> SearchResult<'a> {
+        self.search(game, &SearchLimits::new().with_movetime(budget))
+    }
+
+    pub fn select_move_with_clock<'a>(
+        &mut self,
+        game: &'a Game,
+        time_control: &TimeControl,
+    ) -> SearchResult<'a> {
+        self.search(game, &SearchLimits::new().with_time_control(time_control))
+    }
+
+    /// Searches on all the threads until a limit is reached or the search is stopped, and returns
+    /// the result of the deepest depth completed by the main thread.
+    pub fn search<'a>(&mut self, game: &'a Game, limits: &SearchLimits) -> SearchResult<'a> {
+        self.start_search(limits);
+        let mut helpers: Vec<Engine> = (1..self.threads).map(|_| self.helper()).collect();
+        let pool = self.pool.take();
+        let (score, pv) = match pool {
+            Some(ref pool) => pool.in_place_scope(|scope| {
+                for (index, helper) in helpers.iter_mut().enumerate() {
+                    // Half of the helpers start one ply deeper, to search ahead of the others
+                    let first_depth = 1 + (index % 2) as i32;
+                    scope.spawn(move |_| {
+                        helper.deepen(game, first_depth, limits);
+                    });
                 }
-                if result < beta {
-                    beta = result;
-                    m = Some(candidate);
+                let result = self.deepen(game, 1, limits);
+                self.stop.store(true, Ordering::Relaxed);
+                result
+            }),
+            None => self.deepen(game, 1, limits),
+        };
+        self.pool = pool;
+        self.stop.store(false, Ordering::Relaxed);
+        self.deadline = None;
+        self.node_limit = None;
+        for helper in helpers.iter() {
+            self.stats.add(&helper.stats);
+        }
+        SearchResult::new(game, score, pv)
+    }
+
+    /// Searches one ply deeper at a time, so that each depth is ordered by the previous ones.
+    fn deepen(
+        &mut self,
+        game: &Game,
+        first_depth: i32,
+        limits: &SearchLimits,
+    ) -> (i32, Vec<OwnedMove>) {
+        let start = Instant::now();
+        let max_depth = limits.depth().unwrap_or(MAX_DEPTH).min(MAX_DEPTH);
+        let mut result = (0, Vec::new());
+        for depth in first_depth..=max_depth {
+            let mut pv = Vec::new();
+            let previous_score = Some(result.0).filter(|_| depth > first_depth);
+            let score = self.aspiration_search(game, depth, previous_score, &mut pv);
+            if self.stopped {
+                break;
+            }
+            let finished = pv.is_empty();
+            result = (score, pv);
+            self.report(Some(result.0), &result.1);
+            self.interruptible = true;
+            let mate_found = limits.mate().is_some_and(|moves| {
+                moves_to_mate(score).is_some_and(|found| found > 0 && found <= moves)
+            });
+            // Each depth takes longer than all the previous ones: the next one would not complete
+            let out_of_time = limits
+                .movetime()
+                .is_some_and(|movetime| start.elapsed() >= movetime / 2);
+            if finished || mate_found || out_of_time {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Searches the root with a window around the score of the previous iteration, which is
+    /// widened on the side where the score fell outside of it until it fits.
+    fn aspiration_search(
+        &mut self,
+        game: &Game,
+        depth: i32,
+        previous_score: Option<i32>,
+        pv: &mut Vec<OwnedMove>,
+    ) -> i32 {
+        self.root_depth = depth;
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match previous_score
+            .filter(|score| self.config.aspiration_windows() && !is_mate_score(*score))
+        {
+            Some(score) => (score - delta, score + delta),
+            None => (-INFINITY, INFINITY),
+        };
+        loop {
+            let score = self.alpha_beta(game, alpha, beta, depth, 0, None, pv);
+            if self.stopped {
+                return score;
+            }
+            if score <= alpha {
+                alpha = (score - delta).max(-INFINITY);
+            } else if score >= beta {
+                beta = (score + delta).min(INFINITY);
+            } else {
+                return score;
+            }
+            delta *= 2;
+            if delta > MAX_ASPIRATION_WINDOW {
+                alpha = -INFINITY;
+                beta = INFINITY;
+            }
+        }
+    }
+
+    /// Counts the node, and tells whether the search must stop.
+    fn should_stop(&mut self, ply: usize) -> bool {
+        self.stats.nodes += 1;
+        self.stats.seldepth = self.stats.seldepth.max(ply);
+        let clock_check = self.stats.nodes.is_multiple_of(NODES_BETWEEN_CLOCK_CHECKS);
+        if clock_check {
+            self.shared_nodes
+                .fetch_add(NODES_BETWEEN_CLOCK_CHECKS, Ordering::Relaxed);
+            if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+                self.report(None, &[]);
+            }
+        }
+        if self.interruptible
+            && (self.stop.load(Ordering::Relaxed)
+                || self
+                    .node_limit
+                    .is_some_and(|limit| self.stats.nodes >= limit)
+                || (clock_check
+                    && self
+                        .deadline
+                        .is_some_and(|deadline| Instant::now() >= deadline)))
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    /// Calls the progress callback, without score nor PV while the depth is being searched.
+    fn report(&mut self, score: Option<i32>, pv: &[OwnedMove]) {
+        if self.progress.is_none() {
+            return;
+        }
+        self.last_report = Instant::now();
+        let info = SearchInfo {
+            depth: self.root_depth,
+            seldepth: self.stats.seldepth,
+            nodes: self.shared_nodes.load(Ordering::Relaxed)
+                + self.stats.nodes % NODES_BETWEEN_CLOCK_CHECKS,
+            elapsed: self.start.elapsed(),
+            hashfull: self.transposition_table.hashfull(),
+            score,
+            pv: pv.to_vec(),
+        };
+        if let Some(progress) = self.progress.as_mut() {
+            progress(&info);
+        }
+    }
+
+    fn order<'a>(
+        &self,
+        moves: Vector<Move<'a>>,
+        hash_move: Option<OwnedMove>,
+        ply: usize,
+        previous: Option<OwnedMove>,
+    ) -> Vec<Move<'a>> {
+        if self.config.move_ordering() {
+            self.ordering.order(moves, hash_move, ply, previous)
+        } else {
+            moves.into_iter().collect()
+        }
+    }
+
+    fn record_cutoff(
+        &mut self,
+        mv: OwnedMove,
+        index: usize,
+        depth: i32,
+        ply: usize,
+        previous: Option<OwnedMove>,
+    ) {
+        self.stats.cutoffs += 1;
+        if index == 0 {
+            self.stats.first_move_cutoffs += 1;
+        }
+        self.ordering.update(mv, depth, ply, previous);
+    }
+
+    /// Negamax: scores are from the point of view of the side to move. The previous move is the
+    /// one which led to this position, if any. The principal variation is left in `pv` when the
+    /// score is within the window, and is empty otherwise.
+    #[allow(clippy::too_many_arguments)]
+    fn alpha_beta(
+        &mut self,
+        game: &Game,
+        alpha: i32,
+        beta: i32,
+        depth: i32,
+        ply: usize,
+        previous: Option<OwnedMove>,
+        pv: &mut Vec<OwnedMove>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop(ply) {
+            return 0;
+        }
+        if depth <= 0 {
+            return self.quiescence(game, alpha, beta, ply);
+        }
+        // Nothing here can beat mating on the next move, or be worse than being mated now
+        if mate_in(ply + 1) <= alpha {
+            return alpha;
+        }
+        if mated_in(ply) >= beta {
+            return beta;
+        }
+        let entry = self.transposition_table.probe(game.hash());
+        // The root is always searched, so that the whole principal variation is known
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth() >= depth) {
+            let score = score_from_table(entry.score(), ply);
+            match entry.bound() {
+                Bound::Exact => {
+                    self.table_pv(game, entry.depth(), pv);
+                    return score;
                 }
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
             }
-            (m, beta)
+        }
+        let in_check = game.is_king_check();
+        let mut child_pv = Vec::new();
+        let config = self.config;
+        // Positions are only pruned away from the root, and when the bounds are not mates
+        let static_eval = if ply > 0
+            && !in_check
+            && (config.null_move() || config.futility() || config.razoring())
+        {
+            Some(evaluate(game))
         } else {
-            let mut alpha = alpha;
-            let mut m = None;
-            for candidate in candidates {
-                let (_, result) = alpha_beta(&candidate.new_game(), alpha, beta, depth - 1);
-                if result >= beta {
-                    return (None, beta);
+            None
+        };
+        if let Some(eval) = static_eval {
+            if config.razoring()
+                && depth <= 2
+                && !is_mate_score(alpha)
+                && eval + RAZORING_MARGINS[depth as usize] < alpha
+            {
+                let score = self.quiescence(game, alpha, beta, ply);
+                if score < alpha {
+                    return score;
+                }
+            }
+            // Passing twice in a row is not allowed, and passing without pieces other than pawns
+            // could hide a zugzwang
+            if config.null_move()
+                && depth >= 2
+                && !is_mate_score(beta)
+                && eval >= beta
+                && previous.is_some()
+                && has_pieces(game)
+            {
+                let score = -self.alpha_beta(
+                    &game.pass(),
+                    -beta,
+                    -beta + 1,
+                    depth - 1 - NULL_MOVE_REDUCTION,
+                    ply + 1,
+                    None,
+                    &mut child_pv,
+                );
+                if self.stopped {
+                    return 0;
                 }
-                if result > alpha {
-                    alpha = result;
-                    m = Some(candidate);
+                if score >= beta {
+                    return beta;
                 }
             }
-            (m, alpha)
         }
+        let futility_limit = static_eval
+            .filter(|_| config.futility() && depth <= 2 && !is_mate_score(alpha))
+            .map(|eval| eval + FUTILITY_MARGINS[depth as usize])
+            .filter(|limit| *limit <= alpha);
+        let candidates = game.list_moves();
+        if candidates.is_empty() {
+            return if in_check { mated_in(ply) } else { 0 };
+        }
+        let hash_move = entry.and_then(|entry| entry.best_move());
+        let candidates = self.order(candidates, hash_move, ply, previous);
+        // The extensions of a line stop once it is searched that many plies beyond the nominal
+        // depth, reductions included
+        let can_extend = ply as i32 + depth < self.root_depth + config.max_extensions();
+        let singular_move = entry
+            .filter(|entry| {
+                config.singular_extension()
+                    && can_extend
+                    && ply > 0
+                    && depth >= SINGULAR_MIN_DEPTH
+                    && entry.depth() >= depth - 3
+                    && entry.bound() != Bound::Upper
+                    && !is_mate_score(entry.score())
+            })
+            .and_then(|entry| {
+                let singular_beta = score_from_table(entry.score(), ply) - SINGULAR_MARGIN * depth;
+                entry.best_move().filter(|hash_move| {
+                    self.is_singular(&candidates, *hash_move, singular_beta, depth / 2, ply)
+                })
+            });
+        if self.stopped {
+            return 0;
+        }
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for (index, candidate) in candidates.into_iter().enumerate() {
+            let mv = candidate.owned();
+            let child = candidate.new_game();
+            let gives_check = child.is_king_check();
+            let extended = can_extend
+                && ((config.check_extension() && gives_check)
+                    || (config.recapture_extension() && is_recapture(mv, previous))
+                    || (config.pawn_push_extension() && is_pawn_push_to_seventh(mv))
+                    || Some(mv) == singular_move);
+            let new_depth = if extended {
+                self.stats.extensions += 1;
+                if Some(mv) == singular_move {
+                    self.stats.singular_extensions += 1;
+                }
+                depth
+            } else {
+                depth - 1
+            };
+            let quiet = index > 0 && is_quiet(mv) && !gives_check && !extended;
+            if let Some(limit) = futility_limit.filter(|_| quiet) {
+                best_score = best_score.max(limit);
+                continue;
+            }
+            let reduction = if config.late_move_reductions()
+                && quiet
+                && !in_check
+                && depth >= 3
+                && index >= FULL_DEPTH_MOVES
+            {
+                1
+            } else {
+                0
+            };
+            // With a null window, the moves after the first one are only proven not better
+            let search_beta = if config.principal_variation_search() && index > 0 {
+                alpha + 1
+            } else {
+                beta
+            };
+            let mut score = -self.score_child(
+                &child,
+                mv,
+                -search_beta,
+                -alpha,
+                new_depth - reduction,
+                ply + 1,
+                &mut child_pv,
+            );
+            if reduction > 0 && score > alpha && !self.stopped {
+                score = -self.score_child(
+                    &child,
+                    mv,
+                    -search_beta,
+                    -alpha,
+                    new_depth,
+                    ply + 1,
+                    &mut child_pv,
+                );
+            }
+            if search_beta < beta && score > alpha && score < beta && !self.stopped {
+                score =
+                    -self.score_child(&child, mv, -beta, -alpha, new_depth, ply + 1, &mut child_pv);
+            }
+            if self.stopped {
+                return 0;
+            }
+            best_score = best_score.max(score);
+            if score >= beta {
+                best_move = Some(mv);
+                pv.clear();
+                self.record_cutoff(mv, index, depth, ply, previous);
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                set_pv(pv, mv, &child_pv);
+            }
+        }
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_move.is_some() {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.transposition_table.store(
+            game.hash(),
+            depth,
+            bound,
+            score_to_table(best_score, ply),
+            best_move.or(hash_move),
+        );
+        best_score
     }
-}
 
-fn score_game(game: &Game) -> i32 {
-    if game.is_stalemate() {
-        0
-    } else if game.is_mate() {
-        match game.turn() {
-            Player::White => -20000,
-            Player::Black => 20000,
+    /// Whether all the moves other than the hash move score below `beta`, when searched to the
+    /// given depth.
+    fn is_singular(
+        &mut self,
+        candidates: &[Move],
+        hash_move: OwnedMove,
+        beta: i32,
+        depth: i32,
+        ply: usize,
+    ) -> bool {
+        let mut pv = Vec::new();
+        for candidate in candidates {
+            let mv = candidate.owned();
+            if mv == hash_move {
+                continue;
+            }
+            let child = candidate.new_game();
+            let score =
+                -self.score_child(&child, mv, -beta, -beta + 1, depth - 1, ply + 1, &mut pv);
+            if self.stopped || score >= beta {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Line made of the best moves stored in the transposition table, from the given position.
+    fn table_pv(&self, game: &Game, depth: i32, pv: &mut Vec<OwnedMove>) {
+        let mut game = game.clone();
+        for _ in 0..depth {
+            let entry = self.transposition_table.probe(game.hash());
+            game = match find_move(&game, entry.and_then(|entry| entry.best_move())) {
+                Some(mv) => {
+                    pv.push(mv.owned());
+                    mv.new_game()
+                }
+                None => break,
+            };
+        }
+    }
+
+    /// Plays the captures left at the end of the search, so that only quiet positions are
+    /// evaluated. Unless in check, the side to move may also stand pat on the current score.
+    fn quiescence(&mut self, game: &Game, alpha: i32, beta: i32, ply: usize) -> i32 {
+        if self.should_stop(ply) {
+            return 0;
+        }
+        self.stats.quiescence_nodes += 1;
+        let in_check = game.is_king_check();
+        let candidates = if in_check {
+            game.list_moves()
+        } else {
+            game.list_captures()
+        };
+        if in_check && candidates.is_empty() {
+            return mated_in(ply);
+        }
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+        if !in_check {
+            best_score = evaluate(game);
+            if best_score >= beta {
+                return best_score;
+            }
+            alpha = alpha.max(best_score);
+        }
+        for candidate in self.order(candidates, None, ply, None) {
+            let child = candidate.new_game();
+            let score = if is_search_draw(&child) {
+                0
+            } else {
+                -self.quiescence(&child, -beta, -alpha, ply + 1)
+            };
+            if self.stopped {
+                return 0;
+            }
+            best_score = best_score.max(score);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
         }
+        best_score
+    }
+
+    /// Score of the position after the candidate, from the point of view of the opponent.
+    #[allow(clippy::too_many_arguments)]
+    fn score_child(
+        &mut self,
+        child: &Game,
+        mv: OwnedMove,
+        alpha: i32,
+        beta: i32,
+        depth: i32,
+        ply: usize,
+        pv: &mut Vec<OwnedMove>,
+    ) -> i32 {
+        if is_search_draw(child) {
+            pv.clear();
+            0
+        } else {
+            self.alpha_beta(child, alpha, beta, depth, ply, Some(mv), pv)
+        }
+    }
+}
+
+/// Moves until mate for a mate score, negative when the side to move is getting mated.
+pub fn moves_to_mate(score: i32) -> Option<i32> {
+    if score > MATE - MAX_MATE_PLY {
+        Some((MATE - score + 1) / 2)
+    } else if score < MAX_MATE_PLY - MATE {
+        Some(-(MATE + score + 1) / 2)
     } else {
-        let mut result = game
-            .list_pieces()
-            .into_iter()
-            .map(|piece| score_piece(piece))
-            .sum();
-        result += score_moves(game);
-        result
+        None
+    }
+}
+
+fn is_mate_score(score: i32) -> bool {
+    score.abs() > MATE - MAX_MATE_PLY
+}
+
+fn mate_in(ply: usize) -> i32 {
+    MATE - ply as i32
+}
+
+fn mated_in(ply: usize) -> i32 {
+    ply as i32 - MATE
+}
+
+/// Mate scores are stored relative to the position rather than to the root of the search, so
+/// that they stay valid when the position is reached at another ply.
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score > MATE - MAX_MATE_PLY {
+        score + ply as i32
+    } else if score < MAX_MATE_PLY - MATE {
+        score - ply as i32
+    } else {
+        score
     }
 }
 
-fn score_moves(game: &Game) -> i32 {
-    ((game.set_turn(Player::White)).list_moves().len() as i32
-        - (game.set_turn(Player::Black)).list_moves().len() as i32)
-        * 10
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score > MATE - MAX_MATE_PLY {
+        score - ply as i32
+    } else if score < MAX_MATE_PLY - MATE {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+fn is_recapture(mv: OwnedMove, previous: Option<OwnedMove>) -> bool {
+    mv.captured().is_some()
+        && previous
+            .is_some_and(|previous| previous.captured().is_some() && previous.to() == mv.to())
+}
+
+fn is_pawn_push_to_seventh(mv: OwnedMove) -> bool {
+    let seventh = match mv.piece().player() {
+        Player::White => b'7',
+        Player::Black => b'2',
+    };
+    mv.piece().piece_type() == PieceType::Pawn && mv.to().row() == seventh
+}
+
+/// Whether the side to move has pieces other than pawns.
+fn has_pieces(game: &Game) -> bool {
+    let player = game.turn();
+    let board = game.board();
+    let pawns_and_king = board.pieces(Piece::new(PieceType::Pawn, player))
+        | board.pieces(Piece::new(PieceType::King, player));
+    board.player_occupancy(player) & !pawns_and_king != 0
+}
+
+fn set_pv(pv: &mut Vec<OwnedMove>, mv: OwnedMove, child_pv: &[OwnedMove]) {
+    pv.clear();
+    pv.push(mv);
+    pv.extend_from_slice(child_pv);
+}
+
+fn find_move(game: &Game, owned: Option<OwnedMove>) -> Option<Move<'_>> {
+    owned.and_then(|owned| game.find_move(owned))
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws the search can claim, unlike the automatic ones of `Game::is_draw`: one repetition and the
+/// fifty-move rule. If repeating the position was good for the side to move, it will be just as
+/// good the next time. Stalemate is left to the move generation.
+fn is_search_draw(game: &Game) -> bool {
+    game.repetition_count() > 1
+        || game.is_insufficient_material()
+        || (game.is_fifty_move_draw() && !game.is_mate())
+}
+
+/// Static evaluation from the point of view of the side to move.
+fn evaluate(game: &Game) -> i32 {
+    match game.turn() {
+        Player::White => score_game(game),
+        Player::Black => -score_game(game),
+    }
+}
+
+/// Static evaluation from the point of view of White, without generating moves: mates and draws
+/// are found by the search.
+fn score_game(game: &Game) -> i32 {
+    let board = game.board();
+    board
+        .iter()
+        .map(|(position, piece)| {
+            let mobility = if piece.piece_type() == PieceType::Pawn {
+                0
+            } else {
+                let targets = attacks(*piece, *position, board.occupancy())
+                    & !board.player_occupancy(piece.player());
+                targets.count_ones() as i32 * MOBILITY_WEIGHT
+            };
+            match piece.player() {
+                Player::White => score_piece(*piece) + mobility,
+                Player::Black => score_piece(*piece) - mobility,
+            }
+        })
+        .sum()
 }
 
 fn score_piece(piece: Piece) -> i32 {
@@ -92,13 +1030,16 @@ fn score_piece(piece: Piece) -> i32 {
     abs_score * direction
 }
 
-enum Tree {
-    Leaf { game: Game, score: i32 },
-    Node { children: Vector<Tree> },
-}
-
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use board::Player;
+    use board::Position;
     use engine::*;
     use game::Game;
 
@@ -106,4 +1047,632 @@ mod tests {
     fn simple_run() {
         select_move(&Game::new(), 1);
     }
+
+    #[test]
+    fn transposition_table_keeps_result() {
+        // Given
+        let game =
+            Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        let expected = select_move(&game, 3);
+
+        // When
+        let result = engine.select_move(&game, 3);
+
+        // Then
+        let (best_move, score) = (result.best_move(), result.score());
+        assert_eq!(score, expected.score());
+        assert_eq!(
+            best_move.map(|mv| mv.owned()),
+            expected.best_move().map(|mv| mv.owned())
+        );
+        let entry = engine.transposition_table().probe(game.hash()).unwrap();
+        assert_eq!(entry.depth(), 3);
+        assert_eq!(entry.bound(), Bound::Exact);
+        assert_eq!(entry.best_move(), best_move.map(|mv| mv.owned()));
+        let child = best_move.unwrap().new_game();
+        assert_eq!(
+            engine
+                .transposition_table()
+                .probe(child.hash())
+                .map(|entry| entry.depth()),
+            Some(2)
+        );
+        assert!(engine.transposition_table().hashfull() > 0);
+        assert_eq!(engine.select_move(&game, 3).score(), score);
+    }
+
+    #[test]
+    fn principal_variation() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = Engine::with_hash_size(1).select_move(&game, 3);
+
+        // Then
+        assert_eq!(result.pv().len(), 3);
+        assert_eq!(
+            result.best_move().map(|mv| mv.owned()),
+            result.pv().first().cloned()
+        );
+        let end = result
+            .pv()
+            .iter()
+            .fold(game.clone(), |game, mv| game.apply(*mv).unwrap());
+        assert_eq!(score_game(&end), result.score());
+    }
+
+    #[test]
+    fn principal_variation_ends_with_mate() {
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let result = Engine::with_hash_size(1).select_move(&game, 3);
+        let pv: Vec<String> = result.pv().iter().map(|mv| mv.to_string()).collect();
+        assert_eq!(pv, ["a1a8"]);
+    }
+
+    #[test]
+    fn mate_in_two() {
+        // Given
+        let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+
+        // When
+        let result = Engine::with_hash_size(1).select_move(&game, 4);
+
+        // Then
+        assert_eq!(result.score(), MATE - 3);
+        assert_eq!(result.mate(), Some(2));
+        assert_eq!(result.pv().len(), 3);
+    }
+
+    #[test]
+    fn getting_mated() {
+        // Given
+        let game = Game::from_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap();
+
+        // When
+        let result = Engine::with_hash_size(1).select_move(&game, 3);
+
+        // Then
+        assert_eq!(result.score(), 2 - MATE);
+        assert_eq!(result.mate(), Some(-1));
+        assert_eq!(
+            result.best_move().map(|mv| mv.to_string()),
+            Some("a8b8".to_string())
+        );
+    }
+
+    #[test]
+    fn mate_scores_in_table_are_relative_to_position() {
+        assert_eq!(
+            score_from_table(score_to_table(mate_in(5), 2), 4),
+            mate_in(7)
+        );
+        assert_eq!(
+            score_from_table(score_to_table(mated_in(4), 1), 3),
+            mated_in(6)
+        );
+        assert_eq!(score_from_table(score_to_table(150, 1), 3), 150);
+    }
+
+    #[test]
+    fn mate_reporting() {
+        assert_eq!(moves_to_mate(mate_in(1)), Some(1));
+        assert_eq!(moves_to_mate(mate_in(3)), Some(2));
+        assert_eq!(moves_to_mate(mated_in(0)), Some(0));
+        assert_eq!(moves_to_mate(mated_in(2)), Some(-1));
+        assert_eq!(moves_to_mate(-900), None);
+    }
+
+    #[test]
+    fn iterative_deepening() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+
+        // When
+        let start = Instant::now();
+        let best_move = engine
+            .select_move_in_time(&game, Duration::from_millis(300))
+            .best_move();
+
+        // Then
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(best_move.is_some());
+        assert!(
+            engine
+                .transposition_table()
+                .probe(game.hash())
+                .unwrap()
+                .depth()
+                >= 2
+        );
+    }
+
+    #[test]
+    fn first_depth_always_completes() {
+        // Given
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+
+        // When
+        let result = engine.select_move_in_time(&game, Duration::from_secs(0));
+
+        // Then
+        assert_eq!(
+            result.best_move().map(|mv| mv.to_string()),
+            Some("a1a8".to_string())
+        );
+        assert_eq!(result.score(), MATE - 1);
+    }
+
+    #[test]
+    fn with_clock() {
+        // Given
+        let game = Game::new();
+        let time_control =
+            TimeControl::new(Duration::from_millis(100), Duration::from_secs(0), None);
+
+        // When
+        let result = Engine::with_hash_size(1).select_move_with_clock(&game, &time_control);
+
+        // Then
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn no_capture_of_defended_pawn() {
+        // Given
+        let game = Game::from_fen("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+
+        // When
+        let best_move = select_move(&game, 1).best_move();
+
+        // Then
+        assert_ne!(best_move.map(|mv| mv.to_string()), Some("d1d5".to_string()));
+    }
+
+    #[test]
+    fn quiescence_resolves_exchange() {
+        // Given
+        let game = Game::from_fen("4k3/8/4p3/3p4/8/8/8/3QK3 b - - 0 1").unwrap();
+        let after_capture = game
+            .set_turn(Player::White)
+            .parse_uci_move("d1d5")
+            .unwrap()
+            .new_game();
+
+        // When
+        let result = Engine::with_hash_size(0).quiescence(&after_capture, -INFINITY, INFINITY, 0);
+
+        // Then
+        assert!(result > evaluate(&after_capture) + 500);
+        assert!(result > 0);
+    }
+
+    #[test]
+    fn quiet_position_stands_pat() {
+        let game = Game::new();
+        let result = Engine::with_hash_size(0).quiescence(&game, -INFINITY, INFINITY, 0);
+        assert_eq!(result, score_game(&game));
+    }
+
+    #[test]
+    fn move_ordering_reduces_nodes() {
+        // Given
+        let game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let mut unordered = Engine::with_hash_size(1);
+        unordered.set_config(SearchConfig::disabled());
+        let expected_score = unordered.select_move(&game, 3).score();
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_config(SearchConfig::disabled().with_move_ordering(true));
+
+        // When
+        let score = engine.select_move(&game, 3).score();
+
+        // Then
+        assert_eq!(score, expected_score);
+        let stats = engine.stats();
+        let unordered_stats = unordered.stats();
+        assert!(stats.nodes() * 2 < unordered_stats.nodes());
+        assert!(stats.quiescence_nodes() < stats.nodes());
+        assert!(
+            stats.first_move_cutoffs() * unordered_stats.cutoffs()
+                > unordered_stats.first_move_cutoffs() * stats.cutoffs()
+        );
+    }
+
+    #[test]
+    fn pruning_reduces_nodes() {
+        // Given
+        let game = Game::from_fen("6k1/5pp1/4b2p/8/3N4/6P1/5PKP/3R4 w - - 0 1").unwrap();
+        let ordered = SearchConfig::disabled().with_move_ordering(true);
+        let nodes = |config: SearchConfig| {
+            let mut engine = Engine::with_hash_size(1);
+            engine.set_config(config);
+            engine.select_move(&game, 4);
+            engine.stats().nodes()
+        };
+        let expected = nodes(ordered);
+
+        // When
+        let result = [
+            nodes(ordered.with_null_move(true)),
+            nodes(ordered.with_late_move_reductions(true)),
+            nodes(ordered.with_futility(true)),
+            nodes(ordered.with_razoring(true)),
+            nodes(SearchConfig::new()),
+        ];
+
+        // Then
+        for result in result.iter() {
+            assert!(*result < expected);
+        }
+    }
+
+    #[test]
+    fn principal_variation_search() {
+        // Given
+        let game = Game::from_fen("6k1/5pp1/4b2p/8/3N4/6P1/5PKP/3R4 w - - 0 1").unwrap();
+        let ordered = SearchConfig::disabled().with_move_ordering(true);
+        let mut full_window = Engine::with_hash_size(1);
+        full_window.set_config(ordered);
+        let expected = full_window.select_move(&game, 4);
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_config(ordered.with_principal_variation_search(true));
+
+        // When
+        let result = engine.select_move(&game, 4);
+
+        // Then
+        assert_eq!(result.score(), expected.score());
+        assert_eq!(result.pv(), expected.pv());
+        assert!(engine.stats().nodes() < full_window.stats().nodes());
+    }
+
+    #[test]
+    fn aspiration_windows() {
+        // Given
+        let game = Game::from_fen("6k1/5pp1/4b2p/8/3N4/6P1/5PKP/3R4 w - - 0 1").unwrap();
+        let ordered = SearchConfig::disabled().with_move_ordering(true);
+        let mut full_window = Engine::with_hash_size(1);
+        full_window.set_config(ordered);
+        let expected = full_window.select_move(&game, 4);
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_config(ordered.with_aspiration_windows(true));
+
+        // When
+        let result = engine.select_move(&game, 4);
+
+        // Then
+        assert_eq!(result.score(), expected.score());
+        assert_eq!(result.pv(), expected.pv());
+    }
+
+    #[test]
+    fn aspiration_window_widens_up_to_mate() {
+        // Given
+        let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_config(SearchConfig::disabled().with_aspiration_windows(true));
+
+        // When
+        let result = engine.select_move(&game, 3);
+
+        // Then
+        assert_eq!(result.score(), MATE - 3);
+    }
+
+    #[test]
+    fn check_extension() {
+        // Given
+        let game = Game::from_fen("r6k/6pp/7N/8/2Q5/8/8/6K1 w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_config(SearchConfig::disabled().with_check_extension(true));
+
+        // When
+        let result = engine.select_move(&game, 2);
+
+        // Then
+        assert_eq!(result.mate(), Some(2));
+        assert!(engine.stats().extensions() > 0);
+        let mut without_extension = Engine::with_hash_size(1);
+        without_extension.set_config(SearchConfig::disabled());
+        assert_eq!(without_extension.select_move(&game, 2).mate(), None);
+    }
+
+    #[test]
+    fn extension_budget() {
+        // Given
+        let game = Game::from_fen("r6k/6pp/7N/8/2Q5/8/8/6K1 w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        let config = SearchConfig::disabled().with_check_extension(true);
+        engine.set_config(config.with_max_extensions(0));
+
+        // When
+        let result = engine.select_move(&game, 2);
+
+        // Then
+        assert_eq!(result.mate(), None);
+        assert_eq!(engine.stats().extensions(), 0);
+    }
+
+    #[test]
+    fn singular_extension() {
+        // Given
+        let game = Game::from_fen("r6k/6pp/7N/8/2Q5/8/8/6K1 w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+
+        // When
+        engine.select_move(&game, 5);
+
+        // Then
+        assert!(engine.stats().singular_extensions() > 0);
+        engine.set_config(SearchConfig::new().with_singular_extension(false));
+        engine.select_move(&game, 5);
+        assert_eq!(engine.stats().singular_extensions(), 0);
+    }
+
+    #[test]
+    fn recapture_and_pawn_push() {
+        let game = Game::from_fen("4k3/8/1P6/3p4/8/2N1n3/8/4K3 w - - 0 1").unwrap();
+        let mv = |game: &Game, uci| game.parse_uci_move(uci).unwrap().owned();
+        let capture = mv(&game, "c3d5");
+        let after_capture = game.apply(capture).unwrap();
+        assert!(!is_recapture(capture, None));
+        assert!(is_recapture(mv(&after_capture, "e3d5"), Some(capture)));
+        assert!(!is_recapture(mv(&after_capture, "e3c2"), Some(capture)));
+        assert!(is_pawn_push_to_seventh(mv(&game, "b6b7")));
+        assert!(!is_pawn_push_to_seventh(mv(&game, "e1e2")));
+    }
+
+    #[test]
+    fn lazy_smp_finds_same_move() {
+        for (fen, depth) in &[
+            ("r6k/6pp/7N/8/2Q5/8/8/6K1 w - - 0 1", 3),
+            ("k7/8/2K5/8/8/8/8/7R w - - 0 1", 4),
+            ("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 3),
+        ] {
+            // Given
+            let game = Game::from_fen(fen).unwrap();
+            let expected = Engine::with_hash_size(1).select_move(&game, *depth);
+            let mut engine = Engine::with_hash_size(1);
+            engine.set_threads(4);
+
+            // When
+            let result = engine.select_move(&game, *depth);
+
+            // Then
+            assert_eq!(
+                result.best_move().map(|mv| mv.owned()),
+                expected.best_move().map(|mv| mv.owned()),
+                "{}",
+                fen
+            );
+            assert_eq!(result.mate(), expected.mate(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn lazy_smp_tactical_position() {
+        // Given
+        let game =
+            Game::from_fen("r2qk2r/ppp2ppp/2n5/3np3/2B5/5N2/PPP2PPP/R1BQ1RK1 w kq - 0 9").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_threads(4);
+
+        // When
+        let result = engine.select_move(&game, 5);
+
+        // Then
+        // Each move of the principal variation is legal in the position it is played from
+        result
+            .pv()
+            .iter()
+            .fold(game.clone(), |game, mv| game.apply(*mv).unwrap());
+        assert_eq!(
+            result.best_move().map(|mv| mv.owned()),
+            result.pv().first().cloned()
+        );
+        assert!(result.pv().len() >= 4);
+        assert!(!is_mate_score(result.score()));
+    }
+
+    #[test]
+    fn lazy_smp_in_time() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        engine.set_threads(2);
+
+        // When
+        let start = Instant::now();
+        let result = engine.select_move_in_time(&game, Duration::from_millis(200));
+
+        // Then
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(result.best_move().is_some());
+        assert_eq!(engine.threads(), 2);
+    }
+
+    #[test]
+    fn node_limit() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        let limits = SearchLimits::new().with_nodes(1000);
+
+        // When
+        let result = engine.search(&game, &limits);
+
+        // Then
+        assert!(result.best_move().is_some());
+        assert_eq!(engine.stats().nodes, 1000);
+    }
+
+    #[test]
+    fn mate_limit() {
+        // Given
+        let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut engine = Engine::new();
+        let limits = SearchLimits::new().with_mate(2);
+
+        // When
+        let result = engine.search(&game, &limits);
+
+        // Then
+        assert_eq!(result.mate(), Some(2));
+    }
+
+    #[test]
+    fn stop_from_another_thread() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        let handle = engine.stop_handle();
+
+        // When
+        let start = Instant::now();
+        let result = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                handle.stop();
+            });
+            engine.search(&game, &SearchLimits::infinite())
+        });
+
+        // Then
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn progress_after_each_depth() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let progress_infos = Arc::clone(&infos);
+        engine.set_progress(move |info| progress_infos.lock().unwrap().push(info.clone()));
+
+        // When
+        let result = engine.select_move(&game, 3);
+
+        // Then
+        let infos = infos.lock().unwrap();
+        let completed: Vec<&SearchInfo> =
+            infos.iter().filter(|info| info.score().is_some()).collect();
+        assert_eq!(
+            completed
+                .iter()
+                .map(|info| info.depth())
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        let last = completed[2];
+        assert_eq!(last.score(), Some(result.score()));
+        assert_eq!(last.pv(), result.pv());
+        assert_eq!(last.nodes(), engine.stats().nodes());
+        assert!(last.seldepth() >= 3);
+    }
+
+    #[test]
+    fn stop_with_previous_handle() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        let previous = engine.stop_handle();
+        engine.select_move(&game, 1);
+        previous.stop();
+        engine.stop_handle();
+
+        // When
+        engine.select_move(&game, 3);
+
+        // Then
+        assert_eq!(engine.root_depth, 3);
+    }
+
+    #[test]
+    fn stop_before_search() {
+        // Given
+        let game = Game::new();
+        let mut engine = Engine::with_hash_size(1);
+        engine.stop_handle().stop();
+
+        // When
+        let result = engine.search(&game, &SearchLimits::infinite());
+
+        // Then
+        assert!(result.best_move().is_some());
+        assert!(!engine.stop.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn pieces_other_than_pawns() {
+        let has_pieces = |fen| has_pieces(&Game::from_fen(fen).unwrap());
+        assert!(has_pieces("4k3/4p3/8/8/8/8/4P3/4KN2 w - - 0 1"));
+        assert!(!has_pieces("4k3/4p3/8/8/8/8/4P3/4KN2 b - - 0 1"));
+        assert!(!has_pieces("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+    }
+
+    #[test]
+    fn new_game_clears_transposition_table() {
+        // Given
+        let mut engine = Engine::with_hash_size(1);
+        engine.select_move(&Game::new(), 2);
+
+        // When
+        engine.new_game();
+
+        // Then
+        assert_eq!(engine.transposition_table().hashfull(), 0);
+    }
+
+    #[test]
+    fn fifty_move_draw_score() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 99 80").unwrap();
+
+        // When
+        let result = select_move(&game, 1).score();
+
+        // Then
+        assert_eq!(result, 0);
+    }
+
+    #[test]
+    fn insufficient_material_score() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 1").unwrap();
+
+        // When
+        let result = select_move(&game, 1).score();
+
+        // Then
+        assert_eq!(result, 0);
+    }
+
+    #[test]
+    fn repeated_position_is_draw() {
+        // Given
+        let game = ["g1f3", "g8f6", "f3g1", "f6g8"]
+            .iter()
+            .fold(Game::new(), |game, mv| {
+                game.execute_move(
+                    Position::from(&mv[0..2]).unwrap(),
+                    Position::from(&mv[2..4]).unwrap(),
+                )
+                .unwrap()
+            });
+
+        // When
+        let result = is_search_draw(&game);
+
+        // Then
+        assert!(result);
+        assert!(!is_search_draw(&Game::new()));
+    }
 }
@@ -0,0 +1,207 @@
+/// Plies a line may be searched beyond the nominal depth by default.
+const DEFAULT_MAX_EXTENSIONS: i32 = 4;
+
+/// Heuristics used by the search, which can be switched off one by one to measure their effect.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SearchConfig {
+    move_ordering: bool,
+    null_move: bool,
+    late_move_reductions: bool,
+    futility: bool,
+    razoring: bool,
+    principal_variation_search: bool,
+    aspiration_windows: bool,
+    check_extension: bool,
+    recapture_extension: bool,
+    pawn_push_extension: bool,
+    singular_extension: bool,
+    max_extensions: i32,
+}
+
+impl SearchConfig {
+    /// Every heuristic enabled.
+    pub fn new() -> Self {
+        Self {
+            move_ordering: true,
+            null_move: true,
+            late_move_reductions: true,
+            futility: true,
+            razoring: true,
+            principal_variation_search: true,
+            aspiration_windows: true,
+            check_extension: true,
+            recapture_extension: true,
+            pawn_push_extension: true,
+            singular_extension: true,
+            max_extensions: DEFAULT_MAX_EXTENSIONS,
+        }
+    }
+
+    /// Plain alpha-beta, searching every move to the full depth in the generated order.
+    pub fn disabled() -> Self {
+        Self {
+            move_ordering: false,
+            null_move: false,
+            late_move_reductions: false,
+            futility: false,
+            razoring: false,
+            principal_variation_search: false,
+            aspiration_windows: false,
+            check_extension: false,
+            recapture_extension: false,
+            pawn_push_extension: false,
+            singular_extension: false,
+            max_extensions: DEFAULT_MAX_EXTENSIONS,
+        }
+    }
+
+    pub fn move_ordering(&self) -> bool {
+        self.move_ordering
+    }
+
+    /// Skip the search of positions where passing the turn is already too good for the opponent.
+    pub fn null_move(&self) -> bool {
+        self.null_move
+    }
+
+    /// Search the quiet moves coming late in the move ordering less deeply.
+    pub fn late_move_reductions(&self) -> bool {
+        self.late_move_reductions
+    }
+
+    /// Skip quiet moves near the leaves when the position is far below alpha.
+    pub fn futility(&self) -> bool {
+        self.futility
+    }
+
+    /// Drop into the quiescence search near the leaves when the position is far below alpha.
+    pub fn razoring(&self) -> bool {
+        self.razoring
+    }
+
+    /// Search the moves after the first one with a null window, to only prove that they are not
+    /// better, and search them again with the full window when they are.
+    pub fn principal_variation_search(&self) -> bool {
+        self.principal_variation_search
+    }
+
+    /// Start each iteration of the iterative deepening with a window around the previous score.
+    pub fn aspiration_windows(&self) -> bool {
+        self.aspiration_windows
+    }
+
+    /// Search one ply deeper after a move giving check.
+    pub fn check_extension(&self) -> bool {
+        self.check_extension
+    }
+
+    /// Search one ply deeper after a capture back on the square of the previous capture.
+    pub fn recapture_extension(&self) -> bool {
+        self.recapture_extension
+    }
+
+    /// Search one ply deeper after a pawn push to the row before promotion.
+    pub fn pawn_push_extension(&self) -> bool {
+        self.pawn_push_extension
+    }
+
+    /// Search one ply deeper after the hash move when all the other moves are clearly worse.
+    pub fn singular_extension(&self) -> bool {
+        self.singular_extension
+    }
+
+    /// Plies a line may be searched beyond the nominal depth through extensions.
+    pub fn max_extensions(&self) -> i32 {
+        self.max_extensions
+    }
+
+    pub fn with_move_ordering(self, enabled: bool) -> Self {
+        Self {
+            move_ordering: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_null_move(self, enabled: bool) -> Self {
+        Self {
+            null_move: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_late_move_reductions(self, enabled: bool) -> Self {
+        Self {
+            late_move_reductions: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_futility(self, enabled: bool) -> Self {
+        Self {
+            futility: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_razoring(self, enabled: bool) -> Self {
+        Self {
+            razoring: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_principal_variation_search(self, enabled: bool) -> Self {
+        Self {
+            principal_variation_search: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_aspiration_windows(self, enabled: bool) -> Self {
+        Self {
+            aspiration_windows: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_check_extension(self, enabled: bool) -> Self {
+        Self {
+            check_extension: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_recapture_extension(self, enabled: bool) -> Self {
+        Self {
+            recapture_extension: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_pawn_push_extension(self, enabled: bool) -> Self {
+        Self {
+            pawn_push_extension: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_singular_extension(self, enabled: bool) -> Self {
+        Self {
+            singular_extension: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_max_extensions(self, plies: i32) -> Self {
+        Self {
+            max_extensions: plies,
+            ..self
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use engine::time::TimeControl;
+
+/// When to stop searching. The first depth is always completed, so that there is a move.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SearchLimits {
+    depth: Option<i32>,
+    nodes: Option<u64>,
+    movetime: Option<Duration>,
+    infinite: bool,
+    mate: Option<i32>,
+}
+
+impl SearchLimits {
+    /// No limit other than the maximum depth of the engine.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Until stopped: the other limits are ignored.
+    pub fn infinite() -> Self {
+        Self {
+            infinite: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn depth(&self) -> Option<i32> {
+        self.depth.filter(|_| !self.infinite)
+    }
+
+    pub fn nodes(&self) -> Option<u64> {
+        self.nodes.filter(|_| !self.infinite)
+    }
+
+    pub fn movetime(&self) -> Option<Duration> {
+        self.movetime.filter(|_| !self.infinite)
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.infinite
+    }
+
+    /// Moves of the mate to look for: the search stops once a mate that fast is found.
+    pub fn mate(&self) -> Option<i32> {
+        self.mate.filter(|_| !self.infinite)
+    }
+
+    pub fn with_depth(self, depth: i32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..self
+        }
+    }
+
+    pub fn with_nodes(self, nodes: u64) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..self
+        }
+    }
+
+    pub fn with_movetime(self, movetime: Duration) -> Self {
+        Self {
+            movetime: Some(movetime),
+            ..self
+        }
+    }
+
+    /// Time allocated to the move by the clock of the player to move.
+    pub fn with_time_control(self, time_control: &TimeControl) -> Self {
+        self.with_movetime(time_control.allocate())
+    }
+
+    pub fn with_mate(self, moves: i32) -> Self {
+        Self {
+            mate: Some(moves),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use engine::limits::*;
+
+    #[test]
+    fn infinite_ignores_other_limits() {
+        // Given
+        let limits = SearchLimits::infinite().with_depth(3).with_nodes(100);
+
+        // When
+        let result = (limits.depth(), limits.nodes(), limits.is_infinite());
+
+        // Then
+        assert_eq!(result, (None, None, true));
+    }
+
+    #[test]
+    fn time_control() {
+        let time_control =
+            TimeControl::new(Duration::from_secs(60), Duration::from_secs(0), Some(3));
+        let limits = SearchLimits::new().with_time_control(&time_control);
+        assert_eq!(limits.movetime(), Some(Duration::from_secs(20)));
+    }
+}
@@ -0,0 +1,228 @@
+use board::Piece;
+use board::PieceType;
+use game::Move;
+use game::OwnedMove;
+
+const HASH_MOVE: i32 = 1_000_000;
+const CAPTURE: i32 = 100_000;
+const FIRST_KILLER: i32 = 90_000;
+const SECOND_KILLER: i32 = 89_000;
+const COUNTER_MOVE: i32 = 80_000;
+/// History scores are halved once one of them reaches this value, to stay below the counter-move.
+const MAX_HISTORY: i32 = 50_000;
+
+/// Learns which moves were good during the search to try them first in other positions:
+/// killers are quiet moves which caused a cutoff at the same ply, the history scores quiet moves
+/// by the cutoffs they caused anywhere, and the counter-move is the quiet move which last
+/// refuted the same previous move.
+#[derive(Clone)]
+pub struct MoveOrdering {
+    killers: Vec<[Option<OwnedMove>; 2]>,
+    history: [[[i32; 64]; 64]; 2],
+    counter_moves: [[Option<OwnedMove>; 64]; 12],
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            killers: vec![],
+            history: [[[0; 64]; 64]; 2],
+            counter_moves: [[None; 64]; 12],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Keeps what is still relevant to the next search, with less weight.
+    pub fn age(&mut self) {
+        self.killers.clear();
+        for score in self.history.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    /// Hash move first, then captures and promotions from the most valuable victim and the least
+    /// valuable attacker, then killers, the counter-move and other quiet moves by history.
+    pub fn order<'a>(
+        &self,
+        moves: impl IntoIterator<Item = Move<'a>>,
+        hash_move: Option<OwnedMove>,
+        ply: usize,
+        previous: Option<OwnedMove>,
+    ) -> Vec<Move<'a>> {
+        let killers = self.killers.get(ply).cloned().unwrap_or([None; 2]);
+        let counter_move = previous.and_then(|previous| self.counter_move(previous));
+        let mut scored: Vec<(i32, Move<'a>)> = moves
+            .into_iter()
+            .map(|mv| {
+                let owned = mv.owned();
+                let score = if Some(owned) == hash_move {
+                    HASH_MOVE
+                } else if !is_quiet(owned) {
+                    CAPTURE + mvv_lva(owned)
+                } else if Some(owned) == killers[0] {
+                    FIRST_KILLER
+                } else if Some(owned) == killers[1] {
+                    SECOND_KILLER
+                } else if Some(owned) == counter_move {
+                    COUNTER_MOVE
+                } else {
+                    self.history[owned.piece().player() as usize][owned.from().index()]
+                        [owned.to().index()]
+                };
+                (score, mv)
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| -score);
+        scored.into_iter().map(|(_, mv)| mv).collect()
+    }
+
+    /// Records a quiet move which caused a beta cutoff.
+    pub fn update(&mut self, mv: OwnedMove, depth: i32, ply: usize, previous: Option<OwnedMove>) {
+        if !is_quiet(mv) {
+            return;
+        }
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+        let player = mv.piece().player() as usize;
+        let score = &mut self.history[player][mv.from().index()][mv.to().index()];
+        *score += depth * depth;
+        if *score >= MAX_HISTORY {
+            for score in self.history.iter_mut().flatten().flatten() {
+                *score /= 2;
+            }
+        }
+        if let Some(previous) = previous {
+            self.counter_moves[piece_index(previous.piece())][previous.to().index()] = Some(mv);
+        }
+    }
+
+    fn counter_move(&self, previous: OwnedMove) -> Option<OwnedMove> {
+        self.counter_moves[piece_index(previous.piece())][previous.to().index()]
+    }
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn is_quiet(mv: OwnedMove) -> bool {
+    mv.captured().is_none() && mv.promotion().is_none()
+}
+
+/// Most valuable victim first, then least valuable attacker.
+pub fn mvv_lva(mv: OwnedMove) -> i32 {
+    let victim = mv.captured().map_or(0, |piece| rank(piece.piece_type()));
+    let promotion = mv.promotion().map_or(0, rank);
+    10 * (victim + promotion) - rank(mv.piece().piece_type())
+}
+
+fn rank(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    piece.player() as usize * 6 + piece.piece_type() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::ordering::*;
+    use game::Game;
+
+    fn order(game: &Game, ordering: &MoveOrdering, hash_move: Option<&str>) -> Vec<String> {
+        let hash_move = hash_move.map(|uci| game.parse_uci_move(uci).unwrap().owned());
+        ordering
+            .order(game.list_moves(), hash_move, 0, None)
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn captures_by_mvv_lva() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/2q1r3/1P1P4/8/8/7K w - - 0 1").unwrap();
+
+        // When
+        let result = order(&game, &MoveOrdering::new(), None);
+
+        // Then
+        assert_eq!(result[0..3], ["b4c5", "d4c5", "d4e5"]);
+    }
+
+    #[test]
+    fn hash_move_first() {
+        let game = Game::from_fen("4k3/8/8/2q1r3/1P1P4/8/8/7K w - - 0 1").unwrap();
+        let result = order(&game, &MoveOrdering::new(), Some("h1g1"));
+        assert_eq!(result[0..2], ["h1g1", "b4c5"]);
+    }
+
+    #[test]
+    fn killers_and_history() {
+        // Given
+        let game = Game::new();
+        let mut ordering = MoveOrdering::new();
+        let mv = |uci| game.parse_uci_move(uci).unwrap().owned();
+        ordering.update(mv("b1c3"), 1, 1, None);
+        ordering.update(mv("g1f3"), 1, 0, None);
+        ordering.update(mv("g2g3"), 1, 0, None);
+
+        // When
+        let result = order(&game, &ordering, None);
+
+        // Then
+        assert_eq!(result[0..3], ["g2g3", "g1f3", "b1c3"]);
+    }
+
+    #[test]
+    fn counter_move() {
+        // Given
+        let game = Game::new();
+        let mut ordering = MoveOrdering::new();
+        let e4 = game.parse_uci_move("e2e4").unwrap();
+        let after_e4 = e4.new_game();
+        let reply = after_e4.parse_uci_move("c7c5").unwrap().owned();
+        ordering.update(reply, 1, 3, Some(e4.owned()));
+        ordering.update(after_e4.parse_uci_move("d7d5").unwrap().owned(), 4, 5, None);
+
+        // When
+        let result = ordering.order(after_e4.list_moves(), None, 1, Some(e4.owned()));
+
+        // Then
+        assert_eq!(result[0].owned(), reply);
+    }
+
+    #[test]
+    fn captures_are_not_killers() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/2q1r3/1P1P4/8/8/7K w - - 0 1").unwrap();
+        let mut ordering = MoveOrdering::new();
+
+        // When
+        ordering.update(game.parse_uci_move("d4e5").unwrap().owned(), 1, 0, None);
+
+        // Then
+        assert_eq!(
+            order(&game, &ordering, None)[0..3],
+            ["b4c5", "d4c5", "d4e5"]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+/// Moves expected until the end of the game when the time control doesn't tell.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Kept on the clock for the time spent outside of the search.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+/// Clock of the player to move.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    remaining: Duration,
+    increment: Duration,
+    moves_to_go: Option<u32>,
+}
+
+impl TimeControl {
+    pub fn new(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        Self {
+            remaining,
+            increment,
+            moves_to_go,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    pub fn moves_to_go(&self) -> Option<u32> {
+        self.moves_to_go
+    }
+
+    /// Time to spend on the next move: an even share of the remaining time until the next time
+    /// control, plus most of the increment.
+    pub fn allocate(&self) -> Duration {
+        let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let share = self.remaining / moves_to_go + self.increment * 3 / 4;
+        share.min(self.remaining.saturating_sub(MOVE_OVERHEAD))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use engine::time::*;
+
+    #[test]
+    fn sudden_death() {
+        // Given
+        let time_control = TimeControl::new(Duration::from_secs(300), Duration::from_secs(0), None);
+
+        // When
+        let result = time_control.allocate();
+
+        // Then
+        assert_eq!(result, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn increment() {
+        let time_control = TimeControl::new(Duration::from_secs(60), Duration::from_secs(4), None);
+        assert_eq!(time_control.allocate(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn moves_to_go() {
+        let time_control =
+            TimeControl::new(Duration::from_secs(60), Duration::from_secs(0), Some(3));
+        assert_eq!(time_control.allocate(), Duration::from_secs(20));
+        let last_move = TimeControl::new(Duration::from_secs(60), Duration::from_secs(0), Some(0));
+        assert_eq!(last_move.allocate(), Duration::from_millis(59_950));
+    }
+
+    #[test]
+    fn never_more_than_remaining() {
+        let time_control =
+            TimeControl::new(Duration::from_millis(100), Duration::from_secs(2), None);
+        assert_eq!(time_control.allocate(), Duration::from_millis(50));
+        let flagging = TimeControl::new(Duration::from_millis(10), Duration::from_secs(0), None);
+        assert_eq!(flagging.allocate(), Duration::from_secs(0));
+    }
+}
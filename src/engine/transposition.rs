@@ -0,0 +1,318 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use game::OwnedMove;
+
+/// How the stored score relates to the real score of the position.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    /// The real score is at least the stored one (the search failed high).
+    Lower,
+    /// The real score is at most the stored one (the search failed low).
+    Upper,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Entry {
+    depth: i32,
+    bound: Bound,
+    score: i32,
+    best_move: Option<OwnedMove>,
+}
+
+impl Entry {
+    pub fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn best_move(&self) -> Option<OwnedMove> {
+        self.best_move
+    }
+
+    /// The whole entry in one word: the move in the low 32 bits, then whether there is a move,
+    /// the score, the depth and the bound, which is never 0 so that empty slots can be told apart.
+    fn to_bits(self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        self.best_move
+            .map_or(0, |mv| u64::from(mv.to_bits()) | 1 << 32)
+            | u64::from(self.score as i16 as u16) << 33
+            | u64::from(self.depth.clamp(0, 255) as u8) << 49
+            | bound << 57
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        Self {
+            depth: i32::from((bits >> 49) as u8),
+            bound: match bits >> 57 & 3 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            score: i32::from((bits >> 33) as u16 as i16),
+            best_move: if bits >> 32 & 1 == 1 {
+                Some(OwnedMove::from_bits(bits as u32))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// The key is stored xored with the data, so that a slot torn by two threads writing it at the
+/// same time does not match any position.
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Search results indexed by position hash, which can be shared by several threads without
+/// locks. Each position has a single slot, shared with the positions whose hash has the same low
+/// bits.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    used: AtomicUsize,
+}
+
+impl TranspositionTable {
+    /// Largest table with a power of two number of entries fitting in the given size.
+    pub fn new(megabytes: usize) -> Self {
+        let capacity = (megabytes * 1024 * 1024 / mem::size_of::<Slot>()).max(1);
+        Self {
+            slots: (0..1 << (63 - (capacity as u64).leading_zeros()))
+                .map(|_| Slot {
+                    key: AtomicU64::new(0),
+                    data: AtomicU64::new(0),
+                })
+                .collect(),
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed);
+        if data != 0 && key ^ data == hash {
+            Some(Entry::from_bits(data))
+        } else {
+            None
+        }
+    }
+
+    /// A deeper result for the same position is only replaced by a result at least as deep.
+    pub fn store(
+        &self,
+        hash: u64,
+        depth: i32,
+        bound: Bound,
+        score: i32,
+        best_move: Option<OwnedMove>,
+    ) {
+        let slot = &self.slots[self.index(hash)];
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.key.load(Ordering::Relaxed);
+        if old_data == 0 {
+            self.used.fetch_add(1, Ordering::Relaxed);
+        } else if old_key ^ old_data == hash && Entry::from_bits(old_data).depth > depth {
+            return;
+        }
+        let data = Entry {
+            depth,
+            bound,
+            score,
+            best_move,
+        }
+        .to_bits();
+        slot.data.store(data, Ordering::Relaxed);
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.data.store(0, Ordering::Relaxed);
+            slot.key.store(0, Ordering::Relaxed);
+        }
+        self.used.store(0, Ordering::Relaxed);
+    }
+
+    /// Used entries per thousand, as reported to UCI interfaces.
+    pub fn hashfull(&self) -> u32 {
+        (self.used.load(Ordering::Relaxed).min(self.slots.len()) * 1000 / self.slots.len()) as u32
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash & (self.slots.len() as u64 - 1)) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use engine::transposition::*;
+    use game::Game;
+
+    #[test]
+    fn size() {
+        assert!(TranspositionTable::new(1).capacity().is_power_of_two());
+        assert!(TranspositionTable::new(1).capacity() > 1000);
+        assert_eq!(
+            TranspositionTable::new(2).capacity(),
+            2 * TranspositionTable::new(1).capacity()
+        );
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn store_and_probe() {
+        // Given
+        let table = TranspositionTable::new(1);
+        let game = Game::new();
+        let best_move = game.parse_uci_move("e2e4").unwrap().owned();
+
+        // When
+        table.store(game.hash(), 3, Bound::Lower, 42, Some(best_move));
+
+        // Then
+        let entry = table.probe(game.hash()).unwrap();
+        assert_eq!(entry.depth(), 3);
+        assert_eq!(entry.bound(), Bound::Lower);
+        assert_eq!(entry.score(), 42);
+        assert_eq!(entry.best_move(), Some(best_move));
+        assert_eq!(table.probe(game.hash() ^ 1), None);
+        assert_eq!(table.probe(game.hash() ^ (table.capacity() as u64)), None);
+    }
+
+    #[test]
+    fn keep_deeper_result() {
+        // Given
+        let table = TranspositionTable::new(1);
+        table.store(7, 5, Bound::Exact, 10, None);
+
+        // When
+        table.store(7, 2, Bound::Exact, 20, None);
+
+        // Then
+        assert_eq!(table.probe(7).map(|entry| entry.score()), Some(10));
+        table.store(7, 5, Bound::Exact, 30, None);
+        assert_eq!(table.probe(7).map(|entry| entry.score()), Some(30));
+    }
+
+    #[test]
+    fn replace_other_position() {
+        // Given
+        let table = TranspositionTable::new(1);
+        let other = 7 + table.capacity() as u64;
+        table.store(7, 5, Bound::Exact, 10, None);
+
+        // When
+        table.store(other, 1, Bound::Exact, 20, None);
+
+        // Then
+        assert_eq!(table.probe(7), None);
+        assert_eq!(table.probe(other).map(|entry| entry.score()), Some(20));
+    }
+
+    #[test]
+    fn negative_and_mate_scores() {
+        let table = TranspositionTable::new(1);
+        table.store(1, 0, Bound::Upper, -20000, None);
+        table.store(2, 64, Bound::Lower, 19990, None);
+        assert_eq!(table.probe(1).map(|entry| entry.score()), Some(-20000));
+        assert_eq!(
+            table.probe(1).map(|entry| entry.bound()),
+            Some(Bound::Upper)
+        );
+        assert_eq!(table.probe(2).map(|entry| entry.score()), Some(19990));
+        assert_eq!(table.probe(2).map(|entry| entry.depth()), Some(64));
+    }
+
+    #[test]
+    fn entry_bits() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            let moves = game
+                .list_moves()
+                .iter()
+                .map(|mv| Some(mv.owned()))
+                .collect::<Vec<_>>();
+            for (index, best_move) in moves.into_iter().chain(Some(None)).enumerate() {
+                for bound in &[Bound::Exact, Bound::Lower, Bound::Upper] {
+                    let entry = Entry {
+                        depth: index as i32,
+                        bound: *bound,
+                        score: if index % 2 == 0 {
+                            20000 - index as i32
+                        } else {
+                            index as i32 - 20000
+                        },
+                        best_move,
+                    };
+                    assert_eq!(Entry::from_bits(entry.to_bits()), entry, "{}", fen);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        // Given
+        let table = TranspositionTable::new(0);
+
+        // When
+        thread::scope(|scope| {
+            for score in 1..=4 {
+                let table = &table;
+                scope.spawn(move || {
+                    for _ in 0..10_000 {
+                        table.store(score as u64, 1, Bound::Exact, score, None);
+                        if let Some(entry) = table.probe(score as u64) {
+                            assert_eq!(entry.score(), score);
+                        }
+                    }
+                });
+            }
+        });
+
+        // Then
+        assert_eq!(table.hashfull(), 1000);
+    }
+
+    #[test]
+    fn hashfull_and_clear() {
+        // Given
+        let table = TranspositionTable::new(0);
+        assert_eq!(table.hashfull(), 0);
+        table.store(1, 1, Bound::Exact, 0, None);
+        assert_eq!(table.hashfull(), 1000);
+
+        // When
+        table.clear();
+
+        // Then
+        assert_eq!(table.hashfull(), 0);
+        assert_eq!(table.probe(1), None);
+    }
+}
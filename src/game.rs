@@ -1,5 +1,7 @@
 use im::Vector;
 
+use attacks::attacks;
+use board::positions;
 use board::Board;
 use board::Piece;
 use board::PieceType;
@@ -7,20 +9,37 @@ use board::Player;
 use board::Position;
 
 mod bishop;
+mod fen;
 mod king;
 mod knight;
 mod move_list;
+mod outcome;
 mod pawn;
+mod perft;
 mod queen;
+mod record;
 mod rook;
+mod san;
+mod uci_move;
+mod zobrist;
 
-#[derive(Clone, Debug, PartialEq)]
+pub use self::fen::{FenError, STARTING_POSITION};
+pub use self::outcome::{Outcome, Termination};
+pub use self::record::GameRecord;
+pub use self::san::SanError;
+pub use self::uci_move::UciMoveError;
+
+#[derive(Clone, Debug)]
 pub struct Game {
     board: Board,
     player_turn: Player,
     en_passant: Option<Position>,
     castle_white: (bool, bool),
     castle_black: (bool, bool),
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    hash: u64,
+    history: Vector<u64>,
 }
 
 impl Game {
@@ -46,7 +65,12 @@ impl Game {
             en_passant: Option::None,
             castle_white: (castle_a_white, castle_h_white),
             castle_black: (castle_a_black, castle_h_black),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         }
+        .with_computed_hash()
     }
 
     pub fn board(&self) -> &Board {
@@ -57,10 +81,38 @@ impl Game {
         self.player_turn
     }
 
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
     pub fn set_turn(&self, player: Player) -> Self {
         Self {
             player_turn: player,
+            hash: if player == self.player_turn {
+                self.hash
+            } else {
+                self.toggle_turn_hash()
+            },
+            ..self.clone()
+        }
+    }
+
+    /// The same position with the opponent to move, as if the player passed. The positions
+    /// reached before are forgotten, as they cannot be repeated through a pass.
+    pub fn pass(&self) -> Self {
+        let result = Self {
+            player_turn: self.player_turn.opponent(),
+            en_passant: None,
+            history: Vector::new(),
             ..self.clone()
+        };
+        Self {
+            hash: self.next_hash(&result),
+            ..result
         }
     }
 
@@ -81,6 +133,34 @@ impl Game {
             .ok_or_else(|| "Illegal move".to_string())
     }
 
+    /// Plays a move, which must be one of the legal moves of this position.
+    pub fn apply(&self, mv: OwnedMove) -> Result<Self, String> {
+        self.find_move(mv)
+            .map(|mv| mv.new_game())
+            .ok_or_else(|| "Illegal move".to_string())
+    }
+
+    /// The legal move of this position matching the owned move, if any. Only the moves of the
+    /// moved piece are generated.
+    pub fn find_move(&self, mv: OwnedMove) -> Option<Move<'_>> {
+        if mv.piece.player() != self.player_turn || self.get_piece_at(mv.from) != Some(&mv.piece) {
+            return None;
+        }
+        let castles = if mv.castle {
+            king::list_castle_moves(self, mv.from, self.player_turn)
+        } else {
+            Vector::new()
+        };
+        (self.list_piece_moves(mv.from, mv.piece) + castles)
+            .into_iter()
+            .find(|candidate| {
+                candidate.to == mv.to
+                    && candidate.promotion == mv.promotion
+                    && candidate.owned() == mv
+            })
+            .filter(|candidate| self.is_legal(candidate))
+    }
+
     fn apply_move_with_en_passant(
         &self,
         from: Position,
@@ -107,12 +187,32 @@ impl Game {
         if piece.player() == self.turn() {
             let new_piece =
                 promotion.map_or(piece, |piece_type| Piece::new(piece_type, piece.player()));
+            let halfmove_clock =
+                if piece.piece_type() == PieceType::Pawn || self.get_piece_at(to).is_some() {
+                    0
+                } else {
+                    self.halfmove_clock + 1
+                };
+            let fullmove_number = match self.turn() {
+                Player::White => self.fullmove_number,
+                Player::Black => self.fullmove_number + 1,
+            };
+            // Positions before a capture or a pawn move can never occur again
+            let history = if halfmove_clock == 0 {
+                Vector::new()
+            } else {
+                let mut history = self.history.clone();
+                history.push_back(self.hash);
+                history
+            };
             Ok(Self {
                 board: self.board.put(to, new_piece).remove(from),
                 player_turn: self.turn().opponent(),
                 en_passant: Option::None,
-                castle_white: self.castle_white,
-                castle_black: self.castle_black,
+                halfmove_clock,
+                fullmove_number,
+                history,
+                ..self.clone()
             })
         } else {
             Err(String::from("Can’t move pieces from the other player"))
@@ -123,49 +223,72 @@ impl Game {
         self.board.get(position)
     }
 
-    pub fn list_moves(&self) -> Vector<Move> {
-        let king_position = self
-            .board
-            .iter()
-            .find(|(_, piece)| {
-                piece.piece_type() == PieceType::King && piece.player() == self.player_turn
-            })
-            .map(|(position, _)| position);
-        let castles = king_position.map_or(Vector::new(), |position| {
-            king::list_castle_moves(self, *position, self.player_turn)
-        });
-        (self.list_moves_no_check() + castles)
-            .into_iter()
-            .filter(|mov| {
-                !Game {
-                    player_turn: self.player_turn,
-                    ..mov.new_game()
+    pub fn list_moves(&self) -> Vector<Move<'_>> {
+        let castles = self
+            .king_position(self.player_turn)
+            .map_or(Vector::new(), |position| {
+                king::list_castle_moves(self, position, self.player_turn)
+            });
+        self.legal_moves(self.list_moves_no_check() + castles)
+    }
+
+    /// Legal captures and promotions, without generating the other moves.
+    pub fn list_captures(&self) -> Vector<Move<'_>> {
+        let captures = positions(self.board.player_occupancy(self.player_turn))
+            .flat_map(move |position| {
+                let piece = *self.get_piece_at(position).unwrap();
+                match piece.piece_type() {
+                    PieceType::Pawn => pawn::list_pawn_captures(self, position, piece.player()),
+                    _ => move_list::generate_captures(self, piece, position),
                 }
-                .is_king_check()
             })
-            .collect()
+            .collect();
+        self.legal_moves(captures)
     }
 
-    fn list_moves_no_check(&self) -> Vector<Move> {
-        self.board
-            .iter()
-            .filter(move |(_, value)| value.player() == self.turn())
-            .flat_map(move |(key, value)| match value.piece_type() {
-                PieceType::Pawn => pawn::list_pawn_moves(self, *key, value.player()),
-                PieceType::Rook => rook::list_rook_moves(self, *key, value.player()),
-                PieceType::Bishop => bishop::list_bishop_moves(self, *key, value.player()),
-                PieceType::Queen => queen::list_queen_moves(self, *key, value.player()),
-                PieceType::Knight => knight::list_knight_moves(self, *key, value.player()),
-                PieceType::King => king::list_king_moves(self, *key, value.player()),
+    fn legal_moves<'a>(&self, moves: Vector<Move<'a>>) -> Vector<Move<'a>> {
+        moves.into_iter().filter(|mov| self.is_legal(mov)).collect()
+    }
+
+    /// Whether the move does not leave the king of the player in check.
+    fn is_legal(&self, mov: &Move) -> bool {
+        !Game {
+            player_turn: self.player_turn,
+            ..mov.new_game()
+        }
+        .is_king_check()
+    }
+
+    fn list_moves_no_check(&self) -> Vector<Move<'_>> {
+        positions(self.board.player_occupancy(self.player_turn))
+            .flat_map(move |position| {
+                self.list_piece_moves(position, *self.get_piece_at(position).unwrap())
             })
             .collect()
     }
 
-    fn create_move(&self, from: Position, to: Position) -> Move {
+    /// Pseudo-legal moves of the piece, without the castles.
+    fn list_piece_moves(&self, position: Position, piece: Piece) -> Vector<Move<'_>> {
+        match piece.piece_type() {
+            PieceType::Pawn => pawn::list_pawn_moves(self, position, piece.player()),
+            PieceType::Rook => rook::list_rook_moves(self, position, piece.player()),
+            PieceType::Bishop => bishop::list_bishop_moves(self, position, piece.player()),
+            PieceType::Queen => queen::list_queen_moves(self, position, piece.player()),
+            PieceType::Knight => knight::list_knight_moves(self, position, piece.player()),
+            PieceType::King => king::list_king_moves(self, position, piece.player()),
+        }
+    }
+
+    fn create_move(&self, from: Position, to: Position) -> Move<'_> {
         Move::new(self, from, to)
     }
 
-    fn create_move_en_passant(&self, from: Position, to: Position, en_passant: Position) -> Move {
+    fn create_move_en_passant(
+        &self,
+        from: Position,
+        to: Position,
+        en_passant: Position,
+    ) -> Move<'_> {
         Move::new_with_en_passant(self, from, to, en_passant)
     }
 
@@ -174,41 +297,40 @@ impl Game {
         from: Position,
         to: Position,
         promotion: PieceType,
-    ) -> Move {
+    ) -> Move<'_> {
         Move::new_with_promotion(self, from, to, promotion)
     }
 
     fn is_check(&self, position: Position) -> bool {
-        let player = self.player_turn;
-        let opp = player.opponent();
-        let mut result = pawn::list_pawn_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::Pawn, opp)));
-        result |= rook::list_rook_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::Rook, opp)));
-        result |= knight::list_knight_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::Knight, opp)));
-        result |= bishop::list_bishop_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::Bishop, opp)));
-        result |= queen::list_queen_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::Queen, opp)));
-        result |= king::list_king_moves(self, position, player)
-            .iter()
-            .any(|m| self.get_piece_at(m.to) == Some(&Piece::new(PieceType::King, opp)));
-        result
+        let occupancy = self.board.occupancy();
+        let attacks_from = |piece_type| {
+            attacks(
+                Piece::new(piece_type, self.player_turn),
+                position,
+                occupancy,
+            )
+        };
+        let pieces = |piece_type| {
+            self.board
+                .pieces(Piece::new(piece_type, self.player_turn.opponent()))
+        };
+        attacks_from(PieceType::Pawn) & pieces(PieceType::Pawn) != 0
+            || attacks_from(PieceType::Knight) & pieces(PieceType::Knight) != 0
+            || attacks_from(PieceType::King) & pieces(PieceType::King) != 0
+            || attacks_from(PieceType::Bishop)
+                & (pieces(PieceType::Bishop) | pieces(PieceType::Queen))
+                != 0
+            || attacks_from(PieceType::Rook) & (pieces(PieceType::Rook) | pieces(PieceType::Queen))
+                != 0
     }
 
-    fn is_king_check(&self) -> bool {
-        self.board
-            .iter()
-            .find(|(_, piece)| {
-                piece.piece_type() == PieceType::King && piece.player() == self.player_turn
-            })
-            .map_or(false, |(position, _)| self.is_check(*position))
+    fn king_position(&self, player: Player) -> Option<Position> {
+        positions(self.board.pieces(Piece::new(PieceType::King, player))).next()
+    }
+
+    pub fn is_king_check(&self) -> bool {
+        self.king_position(self.player_turn)
+            .is_some_and(|position| self.is_check(position))
     }
 
     pub fn is_stalemate(&self) -> bool {
@@ -219,6 +341,61 @@ impl Game {
         self.list_moves().is_empty() && self.is_king_check()
     }
 
+    /// Neither player can checkmate: only kings remain, with at most a single minor piece or with
+    /// bishops all on squares of the same colour.
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces: Vec<(&Position, &Piece)> = self
+            .board
+            .iter()
+            .filter(|(_, piece)| piece.piece_type() != PieceType::King)
+            .collect();
+        match pieces.as_slice() {
+            [] => true,
+            [(_, piece)] => {
+                piece.piece_type() == PieceType::Bishop || piece.piece_type() == PieceType::Knight
+            }
+            [(first_position, _), ..] => pieces.iter().all(|(position, piece)| {
+                piece.piece_type() == PieceType::Bishop
+                    && is_light_square(**position) == is_light_square(**first_position)
+            }),
+        }
+    }
+
+    /// The game is over as a draw without any player having to claim it.
+    pub fn is_draw(&self) -> bool {
+        self.is_insufficient_material()
+            || self.is_fivefold_repetition()
+            || (self.is_seventy_five_move_draw() && !self.is_mate())
+            || self.is_stalemate()
+    }
+
+    /// Number of times the current position occurred in the game, including now.
+    pub fn repetition_count(&self) -> usize {
+        1 + self
+            .history
+            .iter()
+            .filter(|previous| **previous == self.hash)
+            .count()
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
+    }
+
+    /// Fifty moves by each player without a capture or a pawn move: a draw can be claimed.
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Seventy-five moves by each player without a capture or a pawn move: the game is drawn.
+    pub fn is_seventy_five_move_draw(&self) -> bool {
+        self.halfmove_clock >= 150
+    }
+
     fn disable_castle(&self, player: Player) -> Game {
         match player {
             Player::White => Game {
@@ -232,11 +409,59 @@ impl Game {
         }
     }
 
+    fn disable_castle_from(self, position: Position) -> Game {
+        match (position.column(), position.row()) {
+            (b'a', b'1') => Game {
+                castle_white: (false, self.castle_white.1),
+                ..self
+            },
+            (b'h', b'1') => Game {
+                castle_white: (self.castle_white.0, false),
+                ..self
+            },
+            (b'e', b'1') => Game {
+                castle_white: (false, false),
+                ..self
+            },
+            (b'a', b'8') => Game {
+                castle_black: (false, self.castle_black.1),
+                ..self
+            },
+            (b'h', b'8') => Game {
+                castle_black: (self.castle_black.0, false),
+                ..self
+            },
+            (b'e', b'8') => Game {
+                castle_black: (false, false),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
     pub fn list_pieces(&self) -> Vector<Piece> {
         self.board.iter().map(|(_, piece)| *piece).collect()
     }
 }
 
+fn is_light_square(position: Position) -> bool {
+    (position.column() + position.row()) % 2 == 1
+}
+
+/// Games are equal when they are in the same position with the same move clocks, regardless of
+/// how that position was reached.
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.player_turn == other.player_turn
+            && self.en_passant == other.en_passant
+            && self.castle_white == other.castle_white
+            && self.castle_black == other.castle_black
+            && self.halfmove_clock == other.halfmove_clock
+            && self.fullmove_number == other.fullmove_number
+    }
+}
+
 impl Default for Game {
     fn default() -> Self {
         Self::new()
@@ -298,7 +523,7 @@ impl<'a> Move<'a> {
             .game
             .apply_move_with_en_passant(self.from, self.to, self.en_passant, self.promotion)
             .unwrap_or_else(|_| panic!("Invalid move {:?}", self));
-        if Some(self.to) == self.game.en_passant {
+        if self.is_en_passant() {
             let position_to_remove =
                 Position::from_chars(self.to.column() as char, self.from.row() as char).unwrap();
             result = Game {
@@ -306,7 +531,42 @@ impl<'a> Move<'a> {
                 ..result
             };
         }
-        self.finalize_castle(result)
+        let result = self.finalize_castle(result);
+        Game {
+            hash: self.game.next_hash(&result),
+            ..result
+        }
+    }
+
+    pub fn owned(&self) -> OwnedMove {
+        let piece = *self
+            .game
+            .get_piece_at(self.from)
+            .expect("No piece at \"from\" position");
+        let captured = if self.is_en_passant() {
+            Some(Piece::new(PieceType::Pawn, piece.player().opponent()))
+        } else {
+            self.game.get_piece_at(self.to).cloned()
+        };
+        OwnedMove {
+            from: self.from,
+            to: self.to,
+            piece,
+            captured,
+            promotion: self.promotion,
+            en_passant: self.en_passant,
+            castle: self.detect_castle(),
+            en_passant_capture: self.is_en_passant(),
+        }
+    }
+
+    fn is_en_passant(&self) -> bool {
+        Some(self.to) == self.game.en_passant
+            && self
+                .game
+                .get_piece_at(self.from)
+                .map(|piece| piece.piece_type())
+                == Some(PieceType::Pawn)
     }
 
     fn detect_castle(&self) -> bool {
@@ -315,14 +575,14 @@ impl<'a> Move<'a> {
             .board
             .get(self.from)
             .expect("No piece at \"from\" position");
-        self.from.column() == ('e' as u8)
+        self.from.column() == b'e'
             && piece.piece_type() == PieceType::King
-            && (self.to.column() == ('g' as u8) || self.to.column() == ('c' as u8))
+            && (self.to.column() == b'g' || self.to.column() == b'c')
     }
 
     fn finalize_castle(&self, game: Game) -> Game {
-        if self.detect_castle() {
-            let (rook_from, rook_to) = if self.to.column() == ('c' as u8) {
+        let game = if self.detect_castle() {
+            let (rook_from, rook_to) = if self.to.column() == b'c' {
                 (b'a', b'd')
             } else {
                 (b'h', b'f')
@@ -337,42 +597,113 @@ impl<'a> Move<'a> {
                     ),
                 ..game.disable_castle(game.player_turn.opponent())
             }
-        } else if self.from == Position::from("h1").unwrap() {
-            Game {
-                castle_white: (game.castle_white.0, false),
-                ..game
-            }
-        } else if self.from == Position::from("a1").unwrap() {
-            Game {
-                castle_white: (false, game.castle_white.1),
-                ..game
-            }
-        } else if self.from == Position::from("e1").unwrap() {
-            Game {
-                castle_white: (false, false),
-                ..game
-            }
-        } else if self.from == Position::from("h8").unwrap() {
-            Game {
-                castle_black: (game.castle_black.0, false),
-                ..game
-            }
-        } else if self.from == Position::from("a8").unwrap() {
-            Game {
-                castle_black: (false, game.castle_black.1),
-                ..game
-            }
-        } else if self.from == Position::from("e8").unwrap() {
-            Game {
-                castle_black: (false, false),
-                ..game
-            }
         } else {
             game
+        };
+        // Moving a king or a rook, or capturing a rook, loses the corresponding castle rights
+        let game = game.disable_castle_from(self.from);
+        if self.to.column() == b'e' {
+            game
+        } else {
+            game.disable_castle_from(self.to)
+        }
+    }
+}
+
+/// A move detached from the game it was generated from, so it can be stored or sent to another
+/// thread. It can only be applied to that same position, with `Game::apply`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct OwnedMove {
+    from: Position,
+    to: Position,
+    piece: Piece,
+    captured: Option<Piece>,
+    promotion: Option<PieceType>,
+    en_passant: Option<Position>,
+    castle: bool,
+    en_passant_capture: bool,
+}
+
+impl OwnedMove {
+    pub fn from(self) -> Position {
+        self.from
+    }
+
+    pub fn to(self) -> Position {
+        self.to
+    }
+
+    pub fn promotion(self) -> Option<PieceType> {
+        self.promotion
+    }
+
+    pub fn piece(self) -> Piece {
+        self.piece
+    }
+
+    pub fn captured(self) -> Option<Piece> {
+        self.captured
+    }
+
+    pub fn is_castle(self) -> bool {
+        self.castle
+    }
+
+    pub fn is_en_passant(self) -> bool {
+        self.en_passant_capture
+    }
+
+    /// Compact form of the move, which fits in a single atomic word.
+    pub fn to_bits(self) -> u32 {
+        let piece_bits = |piece: Piece| piece.player() as u32 * 6 + piece.piece_type() as u32;
+        self.from.index() as u32
+            | (self.to.index() as u32) << 6
+            | piece_bits(self.piece) << 12
+            | self.captured.map_or(0, |piece| 1 + piece_bits(piece)) << 16
+            | self.promotion.map_or(0, |piece_type| 1 + piece_type as u32) << 20
+            | self
+                .en_passant
+                .map_or(0, |position| 1 + position.index() as u32)
+                << 23
+            | (self.castle as u32) << 30
+            | (self.en_passant_capture as u32) << 31
+    }
+
+    /// Move from its compact form, as returned by `to_bits`.
+    pub fn from_bits(bits: u32) -> Self {
+        let piece =
+            |bits: u32| Piece::new(PIECE_TYPES[bits as usize % 6], PLAYERS[bits as usize / 6]);
+        let optional = |bits: u32| bits.checked_sub(1);
+        Self {
+            from: Position::from_index((bits & 0x3f) as usize),
+            to: Position::from_index((bits >> 6 & 0x3f) as usize),
+            piece: piece(bits >> 12 & 0xf),
+            captured: optional(bits >> 16 & 0xf).map(piece),
+            promotion: optional(bits >> 20 & 0x7).map(|bits| PIECE_TYPES[bits as usize]),
+            en_passant: optional(bits >> 23 & 0x7f).map(|bits| Position::from_index(bits as usize)),
+            castle: bits >> 30 & 1 == 1,
+            en_passant_capture: bits >> 31 == 1,
         }
     }
 }
 
+/// Piece types and players in the order of their discriminants.
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Rook,
+    PieceType::Pawn,
+];
+const PLAYERS: [Player; 2] = [Player::White, Player::Black];
+
+impl<'a> From<Move<'a>> for OwnedMove {
+    fn from(mv: Move<'a>) -> Self {
+        mv.owned()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use im::HashSet;
@@ -678,6 +1009,10 @@ mod tests {
             en_passant: Some(Position::from("d5").unwrap()),
             castle_white: (true, true),
             castle_black: (true, true),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         };
 
         // When
@@ -717,16 +1052,17 @@ mod tests {
             en_passant: Some(Position::from("d5").unwrap()),
             castle_white: (true, true),
             castle_black: (true, true),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         };
 
         // When
         let move_list = game.list_moves_no_check();
-        let result = move_list
-            .iter()
-            .filter(|mv| {
-                mv.from == Position::from("e4").unwrap() && mv.to == Position::from("d5").unwrap()
-            })
-            .next();
+        let result = move_list.iter().find(|mv| {
+            mv.from == Position::from("e4").unwrap() && mv.to == Position::from("d5").unwrap()
+        });
 
         // Then
         let new_board = Board::empty().put(
@@ -739,6 +1075,10 @@ mod tests {
             en_passant: None,
             castle_white: (true, true),
             castle_black: (true, true),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         };
         assert_eq!(result.unwrap().new_game(), expected_new_game);
     }
@@ -926,6 +1266,10 @@ mod tests {
             en_passant: None,
             castle_white: (true, true),
             castle_black: (true, true),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         };
 
         // When
@@ -946,6 +1290,10 @@ mod tests {
             en_passant: None,
             castle_white: (true, true),
             castle_black: (true, true),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vector::new(),
         };
         assert_eq!(result.unwrap(), expected_new_game);
     }
@@ -1101,26 +1449,29 @@ mod tests {
             game.execute_move(Position::from("e1").unwrap(), Position::from("g1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("g1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("f1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            false,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("g1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("f1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                false,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1153,26 +1504,29 @@ mod tests {
             game.execute_move(Position::from("e1").unwrap(), Position::from("c1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("c1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("d1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            false,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("c1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("d1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                false,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1205,26 +1559,30 @@ mod tests {
             game.execute_move(Position::from("e8").unwrap(), Position::from("g8").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("g8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("f8").unwrap(),
-                    Piece::new(PieceType::Rook, Player::Black),
-                ),
-            White,
-            true,
-            true,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            fullmove_number: 2,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("g8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("f8").unwrap(),
+                        Piece::new(PieceType::Rook, Player::Black),
+                    ),
+                White,
+                true,
+                true,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1257,26 +1615,29 @@ mod tests {
             game.execute_move(Position::from("h1").unwrap(), Position::from("g1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("g1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            false,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("g1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                false,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1309,26 +1670,29 @@ mod tests {
             game.execute_move(Position::from("h1").unwrap(), Position::from("g1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("g1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            true,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("g1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                true,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1361,26 +1725,29 @@ mod tests {
             game.execute_move(Position::from("a1").unwrap(), Position::from("b1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("b1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            false,
-            true,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("b1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                false,
+                true,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1413,26 +1780,29 @@ mod tests {
             game.execute_move(Position::from("a1").unwrap(), Position::from("b1").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("b1").unwrap(),
-                    Piece::new(PieceType::Rook, Player::White),
-                ),
-            Black,
-            false,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("b1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    ),
+                Black,
+                false,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1465,26 +1835,30 @@ mod tests {
             game.execute_move(Position::from("h8").unwrap(), Position::from("g8").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("g8").unwrap(),
-                    Piece::new(PieceType::Rook, Player::Black),
-                ),
-            White,
-            false,
-            false,
-            false,
-            false,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            fullmove_number: 2,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("g8").unwrap(),
+                        Piece::new(PieceType::Rook, Player::Black),
+                    ),
+                White,
+                false,
+                false,
+                false,
+                false,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
 
@@ -1517,26 +1891,481 @@ mod tests {
             game.execute_move(Position::from("a8").unwrap(), Position::from("b8").unwrap());
 
         //Then
-        let expected_game = Game::from_board_with_castle(
-            Board::empty()
-                .put(
-                    Position::from("e1").unwrap(),
-                    Piece::new(PieceType::King, Player::White),
-                )
-                .put(
-                    Position::from("e8").unwrap(),
-                    Piece::new(PieceType::King, Player::Black),
-                )
-                .put(
-                    Position::from("b8").unwrap(),
-                    Piece::new(PieceType::Rook, Player::Black),
-                ),
-            White,
-            false,
-            false,
-            false,
-            true,
-        );
+        let expected_game = Game {
+            halfmove_clock: 1,
+            fullmove_number: 2,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    )
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("b8").unwrap(),
+                        Piece::new(PieceType::Rook, Player::Black),
+                    ),
+                White,
+                false,
+                false,
+                false,
+                true,
+            )
+        };
         assert_eq!(result, Ok(expected_game));
     }
+
+    #[test]
+    fn halfmove_clock_increments() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = game
+            .execute_move(Position::from("g1").unwrap(), Position::from("f3").unwrap())
+            .and_then(|game| {
+                game.execute_move(Position::from("g8").unwrap(), Position::from("f6").unwrap())
+            })
+            .unwrap();
+
+        // Then
+        assert_eq!(result.halfmove_clock(), 2);
+        assert_eq!(result.fullmove_number(), 2);
+    }
+
+    #[test]
+    fn halfmove_clock_reset_on_pawn_move() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 12 30").unwrap();
+
+        // When
+        let result = game
+            .execute_move(Position::from("e2").unwrap(), Position::from("e3").unwrap())
+            .unwrap();
+
+        // Then
+        assert_eq!(result.halfmove_clock(), 0);
+        assert_eq!(result.fullmove_number(), 30);
+    }
+
+    #[test]
+    fn halfmove_clock_reset_on_capture() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/4r3/4K3 w - - 12 30").unwrap();
+
+        // When
+        let result = game
+            .execute_move(Position::from("e1").unwrap(), Position::from("e2").unwrap())
+            .unwrap();
+
+        // Then
+        assert_eq!(result.halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn fifty_move_draw() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K1N1 w - - 99 80").unwrap();
+
+        // When
+        let result = game
+            .execute_move(Position::from("g1").unwrap(), Position::from("f3").unwrap())
+            .unwrap();
+
+        // Then
+        assert_that!(game.is_fifty_move_draw()).is_false();
+        assert_that!(result.is_fifty_move_draw()).is_true();
+        assert_that!(result.is_seventy_five_move_draw()).is_false();
+    }
+
+    #[test]
+    fn seventy_five_move_draw() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K1N1 b - - 149 80").unwrap();
+
+        // When
+        let result = game
+            .execute_move(Position::from("e8").unwrap(), Position::from("e7").unwrap())
+            .unwrap();
+
+        // Then
+        assert_that!(result.is_seventy_five_move_draw()).is_true();
+        assert_eq!(result.fullmove_number(), 81);
+    }
+
+    fn play(game: Game, moves: &[(&str, &str)]) -> Game {
+        moves.iter().fold(game, |game, (from, to)| {
+            game.execute_move(Position::from(from).unwrap(), Position::from(to).unwrap())
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        // Given
+        let knight_dance = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+        let game = play(Game::new(), &knight_dance);
+
+        // When
+        let result = play(game.clone(), &knight_dance);
+
+        // Then
+        assert_eq!(game.repetition_count(), 2);
+        assert_that!(game.is_threefold_repetition()).is_false();
+        assert_eq!(result.repetition_count(), 3);
+        assert_that!(result.is_threefold_repetition()).is_true();
+        assert_that!(result.is_fivefold_repetition()).is_false();
+    }
+
+    #[test]
+    fn fivefold_repetition() {
+        // Given
+        let knight_dance = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+
+        // When
+        let result = (0..4).fold(Game::new(), |game, _| play(game, &knight_dance));
+
+        // Then
+        assert_that!(result.is_fivefold_repetition()).is_true();
+    }
+
+    #[test]
+    fn no_repetition_after_pawn_move() {
+        // Given
+        let game = play(Game::new(), &[("g1", "f3"), ("g8", "f6"), ("f3", "g1")]);
+
+        // When
+        let result = play(
+            game,
+            &[("e7", "e6"), ("g1", "f3"), ("e8", "e7"), ("f3", "g1")],
+        );
+
+        // Then
+        assert_eq!(result.repetition_count(), 1);
+    }
+
+    #[test]
+    fn repetition_needs_same_castle_rights() {
+        // Given
+        let game = Game::from_fen("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1").unwrap();
+
+        // When
+        let result = play(
+            game,
+            &[("h1", "h2"), ("a8", "a7"), ("h2", "h1"), ("a7", "a8")],
+        );
+
+        // Then
+        assert_eq!(result.to_fen(), "r3k3/8/8/8/8/8/8/4K2R w - - 4 3");
+        assert_eq!(result.repetition_count(), 1);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for fen in &[
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K1n1 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/B1B1K1b1 w - - 0 1",
+        ] {
+            assert!(
+                Game::from_fen(fen).unwrap().is_insufficient_material(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn sufficient_material() {
+        for fen in &[
+            "4k3/8/8/8/8/8/8/4K1NN w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K1Nb w - - 0 1",
+            "4kb2/8/8/8/8/8/8/3BK3 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+            "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+        ] {
+            assert!(
+                !Game::from_fen(fen).unwrap().is_insufficient_material(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn draw() {
+        assert_that!(Game::from_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 1")
+            .unwrap()
+            .is_draw())
+        .is_true();
+        assert_that!(Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+            .unwrap()
+            .is_draw())
+        .is_true();
+        assert_that!(Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 150 100")
+            .unwrap()
+            .is_draw())
+        .is_true();
+        assert_that!(Game::new().is_draw()).is_false();
+    }
+
+    fn find_move<'a>(game: &'a Game, from: &str, to: &str) -> Move<'a> {
+        game.list_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.from == Position::from(from).unwrap() && mv.to == Position::from(to).unwrap()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn owned_move() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+
+        // When
+        let result = find_move(&game, "d1", "d8").owned();
+
+        // Then
+        assert_eq!(result.from(), Position::from("d1").unwrap());
+        assert_eq!(result.to(), Position::from("d8").unwrap());
+        assert_eq!(result.piece(), Piece::new(PieceType::Queen, White));
+        assert_eq!(result.captured(), None);
+        assert_eq!(result.promotion(), None);
+        assert_that!(result.is_castle()).is_false();
+        assert_that!(result.is_en_passant()).is_false();
+    }
+
+    #[test]
+    fn owned_move_capture() {
+        // Given
+        let game = Game::from_fen("3rk3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+
+        // When
+        let result = find_move(&game, "d1", "d8").owned();
+
+        // Then
+        assert_eq!(result.captured(), Some(Piece::new(PieceType::Rook, Black)));
+    }
+
+    #[test]
+    fn owned_move_en_passant() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        // When
+        let result = find_move(&game, "e5", "d6").owned();
+
+        // Then
+        assert_that!(result.is_en_passant()).is_true();
+        assert_eq!(result.captured(), Some(Piece::new(PieceType::Pawn, Black)));
+        assert_eq!(
+            game.apply(result).unwrap().to_fen(),
+            "4k3/8/3P4/8/8/8/8/4K3 b - - 0 1"
+        );
+    }
+
+    #[test]
+    fn owned_move_castle() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+
+        // When
+        let result = find_move(&game, "e1", "c1").owned();
+
+        // Then
+        assert_that!(result.is_castle()).is_true();
+        assert_eq!(
+            game.apply(result).unwrap().to_fen(),
+            "4k3/8/8/8/8/8/8/2KR4 b - - 1 1"
+        );
+    }
+
+    #[test]
+    fn owned_move_promotion() {
+        // Given
+        let game = Game::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        // When
+        let result: OwnedMove = game
+            .list_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.to == Position::from("b8").unwrap() && mv.promotion == Some(PieceType::Knight)
+            })
+            .unwrap()
+            .into();
+
+        // Then
+        assert_eq!(result.promotion(), Some(PieceType::Knight));
+        assert_eq!(result.captured(), Some(Piece::new(PieceType::Rook, Black)));
+        assert_eq!(
+            game.apply(result).unwrap().to_fen(),
+            "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1"
+        );
+    }
+
+    #[test]
+    fn find_move_matches_legal_moves() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            "8/8/8/KPp4r/8/8/8/6k1 w - c6 0 1",
+        ] {
+            // Given
+            let game = Game::from_fen(fen).unwrap();
+            let legal: Vec<OwnedMove> = game.list_moves().iter().map(Move::owned).collect();
+            // Pseudo-legal moves of this position and of the opponent in the next ones
+            let others: Vec<Game> = legal.iter().map(|mv| game.apply(*mv).unwrap()).collect();
+            let candidates: Vec<OwnedMove> = others
+                .iter()
+                .chain(Some(&game))
+                .flat_map(|other| {
+                    other
+                        .list_moves_no_check()
+                        .iter()
+                        .map(Move::owned)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            // When
+            let found: Vec<bool> = candidates
+                .iter()
+                .map(|mv| game.find_move(*mv).is_some())
+                .collect();
+
+            // Then
+            for mv in legal.iter() {
+                assert_eq!(game.find_move(*mv).map(|found| found.owned()), Some(*mv));
+            }
+            for (mv, found) in candidates.iter().zip(found) {
+                assert_eq!(found, legal.contains(mv), "{} in {}", mv, fen);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_move_of_other_position() {
+        // Given
+        let game = Game::new();
+        let mv = find_move(&game, "e2", "e4").owned();
+        let next_game = game.apply(mv).unwrap();
+
+        // When
+        let result = next_game.apply(mv);
+
+        // Then
+        assert_eq!(result, Err("Illegal move".to_string()));
+    }
+
+    #[test]
+    fn owned_move_outlives_game() {
+        // Given
+        let game = Game::new();
+        let moves: Vec<OwnedMove> = game.list_moves().iter().map(Move::owned).collect();
+        drop(game);
+
+        // When
+        let result = std::thread::spawn(move || moves.len()).join();
+
+        // Then
+        assert_eq!(result.unwrap(), 20);
+    }
+
+    #[test]
+    fn no_en_passant_capture_with_piece() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/4P3/8/8/4Kn2 b - e3 0 1").unwrap();
+
+        // When
+        let result = find_move(&game, "f1", "e3").owned();
+
+        // Then
+        assert_that!(result.is_en_passant()).is_false();
+        assert_eq!(
+            game.apply(result).unwrap().to_fen(),
+            "4k3/8/8/8/4P3/4n3/8/4K3 w - - 1 2"
+        );
+    }
+
+    #[test]
+    fn capture_rook_disables_castle() {
+        // Given
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        // When
+        let result = game
+            .execute_move(Position::from("a1").unwrap(), Position::from("a8").unwrap())
+            .unwrap();
+
+        // Then
+        assert_eq!(result.to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+    }
+
+    #[test]
+    fn pass() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        // When
+        let result = game.pass();
+
+        // Then
+        assert_eq!(result.to_fen(), "4k3/8/8/3pP3/8/8/8/4K3 b - - 0 1");
+        assert_eq!(
+            result.hash(),
+            Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 b - - 0 1")
+                .unwrap()
+                .hash()
+        );
+    }
+
+    #[test]
+    fn owned_move_bits() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            for mv in game.list_moves().iter().map(Move::owned) {
+                assert_eq!(OwnedMove::from_bits(mv.to_bits()), mv);
+            }
+        }
+    }
+
+    #[test]
+    fn list_captures() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            // Given
+            let game = Game::from_fen(fen).unwrap();
+
+            // When
+            let result: Vec<OwnedMove> = game.list_captures().iter().map(Move::owned).collect();
+
+            // Then
+            let expected: Vec<OwnedMove> = game
+                .list_moves()
+                .iter()
+                .map(Move::owned)
+                .filter(|mv| mv.captured().is_some() || mv.promotion().is_some())
+                .collect();
+            assert_that!(result).has_length(expected.len());
+            assert_that!(result).contains_all_of(&expected.iter());
+        }
+    }
 }
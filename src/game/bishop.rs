@@ -2,12 +2,7 @@ use board::*;
 use game::*;
 
 pub fn list_bishop_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
-    move_list::generate_moves(
-        game,
-        player,
-        position,
-        &[(1, 1), (-1, 1), (1, -1), (-1, -1)],
-    )
+    move_list::generate_moves(game, Piece::new(PieceType::Bishop, player), position)
 }
 
 #[cfg(test)]
@@ -0,0 +1,392 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+
+use board::*;
+use game::*;
+
+pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum FenError {
+    FieldCount(usize),
+    PiecePlacement(String),
+    SideToMove(String),
+    CastlingRights(String),
+    EnPassant(String),
+    HalfmoveClock(String),
+    FullmoveNumber(String),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "Expected 6 FEN fields, got {}", count),
+            FenError::PiecePlacement(field) => write!(f, "Invalid piece placement: {}", field),
+            FenError::SideToMove(field) => write!(f, "Invalid side to move: {}", field),
+            FenError::CastlingRights(field) => write!(f, "Invalid castling rights: {}", field),
+            FenError::EnPassant(field) => write!(f, "Invalid en passant square: {}", field),
+            FenError::HalfmoveClock(field) => write!(f, "Invalid halfmove clock: {}", field),
+            FenError::FullmoveNumber(field) => write!(f, "Invalid fullmove number: {}", field),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl Game {
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+        let board = parse_board(fields[0])?;
+        let player_turn = parse_player(fields[1])?;
+        let (castle_white, castle_black) = parse_castle(fields[2])?;
+        let en_passant = parse_en_passant(fields[3], player_turn)?;
+        let halfmove_clock = fields[4]
+            .parse()
+            .map_err(|_| FenError::HalfmoveClock(fields[4].to_string()))?;
+        let fullmove_number = fields[5]
+            .parse()
+            .ok()
+            .filter(|number| *number > 0)
+            .ok_or_else(|| FenError::FullmoveNumber(fields[5].to_string()))?;
+        Ok(Self {
+            board,
+            player_turn,
+            en_passant,
+            castle_white,
+            castle_black,
+            halfmove_clock,
+            fullmove_number,
+            hash: 0,
+            history: Vector::new(),
+        }
+        .with_computed_hash())
+    }
+
+    pub fn to_fen(&self) -> String {
+        let player = match self.player_turn {
+            Player::White => 'w',
+            Player::Black => 'b',
+        };
+        let en_passant = self
+            .en_passant
+            .map_or_else(|| "-".to_string(), |position| position.to_string());
+        format!(
+            "{} {} {} {} {} {}",
+            show_board(&self.board),
+            player,
+            show_castle(self.castle_white, self.castle_black),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+fn parse_board(field: &str) -> Result<Board, FenError> {
+    let error = || FenError::PiecePlacement(field.to_string());
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(error());
+    }
+    let mut board = Board::empty();
+    for (rank, row) in ranks.iter().zip("87654321".chars()) {
+        let mut column = b'a';
+        for c in rank.chars() {
+            if let Some(empty_squares) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                column += empty_squares as u8;
+            } else {
+                let piece = parse_piece(c).ok_or_else(error)?;
+                let position = Position::from_u8(column, row as u8).ok_or_else(error)?;
+                board = board.put(position, piece);
+                column += 1;
+            }
+            if column > b'h' + 1 {
+                return Err(error());
+            }
+        }
+        if column != b'h' + 1 {
+            return Err(error());
+        }
+    }
+    Ok(board)
+}
+
+fn parse_piece(c: char) -> Option<Piece> {
+    let piece_type = match c.to_ascii_lowercase() {
+        'k' => PieceType::King,
+        'q' => PieceType::Queen,
+        'r' => PieceType::Rook,
+        'b' => PieceType::Bishop,
+        'n' => PieceType::Knight,
+        'p' => PieceType::Pawn,
+        _ => return None,
+    };
+    let player = if c.is_ascii_uppercase() {
+        Player::White
+    } else {
+        Player::Black
+    };
+    Some(Piece::new(piece_type, player))
+}
+
+fn parse_player(field: &str) -> Result<Player, FenError> {
+    match field {
+        "w" => Ok(Player::White),
+        "b" => Ok(Player::Black),
+        _ => Err(FenError::SideToMove(field.to_string())),
+    }
+}
+
+type CastleRights = (bool, bool);
+
+fn parse_castle(field: &str) -> Result<(CastleRights, CastleRights), FenError> {
+    if field == "-" {
+        return Ok(((false, false), (false, false)));
+    }
+    let error = || FenError::CastlingRights(field.to_string());
+    if field.is_empty() || field.len() > 4 {
+        return Err(error());
+    }
+    let mut castle_white = (false, false);
+    let mut castle_black = (false, false);
+    for c in field.chars() {
+        let right = match c {
+            'K' => &mut castle_white.1,
+            'Q' => &mut castle_white.0,
+            'k' => &mut castle_black.1,
+            'q' => &mut castle_black.0,
+            _ => return Err(error()),
+        };
+        if *right {
+            return Err(error());
+        }
+        *right = true;
+    }
+    Ok((castle_white, castle_black))
+}
+
+fn parse_en_passant(field: &str, player_turn: Player) -> Result<Option<Position>, FenError> {
+    if field == "-" {
+        return Ok(None);
+    }
+    let expected_row = match player_turn {
+        Player::White => b'6',
+        Player::Black => b'3',
+    };
+    Position::from(field)
+        .filter(|position| position.row() == expected_row)
+        .map(Some)
+        .ok_or_else(|| FenError::EnPassant(field.to_string()))
+}
+
+fn show_board(board: &Board) -> String {
+    let mut result = String::new();
+    for row in "87654321".chars() {
+        let mut empty_squares = 0;
+        for column in "abcdefgh".chars() {
+            match board.get(Position::from_chars(column, row).unwrap()) {
+                Some(piece) => {
+                    if empty_squares > 0 {
+                        result.push_str(&empty_squares.to_string());
+                        empty_squares = 0;
+                    }
+                    result.push(show_piece(*piece));
+                }
+                None => empty_squares += 1,
+            }
+        }
+        if empty_squares > 0 {
+            result.push_str(&empty_squares.to_string());
+        }
+        result.push('/');
+    }
+    result.pop();
+    result
+}
+
+fn show_piece(piece: Piece) -> char {
+    let c = match piece.piece_type() {
+        PieceType::King => 'k',
+        PieceType::Queen => 'q',
+        PieceType::Rook => 'r',
+        PieceType::Bishop => 'b',
+        PieceType::Knight => 'n',
+        PieceType::Pawn => 'p',
+    };
+    match piece.player() {
+        Player::White => c.to_ascii_uppercase(),
+        Player::Black => c,
+    }
+}
+
+fn show_castle(castle_white: CastleRights, castle_black: CastleRights) -> String {
+    let result: String = [
+        (castle_white.1, 'K'),
+        (castle_white.0, 'Q'),
+        (castle_black.1, 'k'),
+        (castle_black.0, 'q'),
+    ]
+    .iter()
+    .filter(|(allowed, _)| *allowed)
+    .map(|(_, c)| *c)
+    .collect();
+    if result.is_empty() {
+        "-".to_string()
+    } else {
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use self::fen::*;
+
+    use board::*;
+    use game::*;
+
+    #[test]
+    fn parse_starting_position() {
+        // When
+        let result = Game::from_fen(STARTING_POSITION);
+
+        // Then
+        assert_eq!(result, Ok(Game::new()));
+    }
+
+    #[test]
+    fn show_starting_position() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = game.to_fen();
+
+        // Then
+        assert_eq!(result, STARTING_POSITION);
+    }
+
+    #[test]
+    fn parse_position() {
+        // Given
+        let fen = "4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 3 42";
+
+        // When
+        let result = Game::from_fen(fen).unwrap();
+
+        // Then
+        let expected = Game {
+            en_passant: Some(Position::from("d6").unwrap()),
+            halfmove_clock: 3,
+            fullmove_number: 42,
+            ..Game::from_board_with_castle(
+                Board::empty()
+                    .put(
+                        Position::from("e8").unwrap(),
+                        Piece::new(PieceType::King, Player::Black),
+                    )
+                    .put(
+                        Position::from("d5").unwrap(),
+                        Piece::new(PieceType::Pawn, Player::Black),
+                    )
+                    .put(
+                        Position::from("e5").unwrap(),
+                        Piece::new(PieceType::Pawn, Player::White),
+                    )
+                    .put(
+                        Position::from("a1").unwrap(),
+                        Piece::new(PieceType::Rook, Player::White),
+                    )
+                    .put(
+                        Position::from("e1").unwrap(),
+                        Piece::new(PieceType::King, Player::White),
+                    ),
+                Player::White,
+                true,
+                false,
+                false,
+                false,
+            )
+        };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        // Given
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq e3 0 17";
+
+        // When
+        let result = Game::from_fen(fen).map(|game| game.to_fen());
+
+        // Then
+        assert_eq!(result, Ok(fen.to_string()));
+    }
+
+    #[test]
+    fn invalid_field_count() {
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/8 w - -"),
+            Err(FenError::FieldCount(4))
+        );
+    }
+
+    #[test]
+    fn invalid_piece_placement() {
+        for placement in &[
+            "8/8/8/8/8/8/8",
+            "8/8/8/8/8/8/8/7",
+            "8/8/8/8/8/8/8/9",
+            "8/8/8/8/8/8/8/4k4",
+            "8/8/8/8/8/8/8/4x3",
+        ] {
+            assert_eq!(
+                Game::from_fen(&format!("{} w - - 0 1", placement)),
+                Err(FenError::PiecePlacement(placement.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_side_to_move() {
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/8 x - - 0 1"),
+            Err(FenError::SideToMove("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_castling_rights() {
+        for castle in &["KK", "KQkqK", "A"] {
+            assert_eq!(
+                Game::from_fen(&format!("8/8/8/8/8/8/8/8 w {} - 0 1", castle)),
+                Err(FenError::CastlingRights(castle.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_en_passant() {
+        for en_passant in &["e4", "e3", "i6"] {
+            assert_eq!(
+                Game::from_fen(&format!("8/8/8/8/8/8/8/8 w - {} 0 1", en_passant)),
+                Err(FenError::EnPassant(en_passant.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_clocks() {
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/8 w - - -1 1"),
+            Err(FenError::HalfmoveClock("-1".to_string()))
+        );
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/8 w - - 0 0"),
+            Err(FenError::FullmoveNumber("0".to_string()))
+        );
+    }
+}
@@ -2,21 +2,7 @@ use board::*;
 use game::*;
 
 pub fn list_king_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
-    move_list::generate_moves_one_square(
-        game,
-        player,
-        position,
-        &[
-            (0, 1),
-            (1, 0),
-            (1, 1),
-            (1, -1),
-            (0, -1),
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-        ],
-    )
+    move_list::generate_moves(game, Piece::new(PieceType::King, player), position)
 }
 
 pub fn list_castle_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
@@ -26,6 +12,9 @@ pub fn list_castle_moves(game: &Game, position: Position, player: Player) -> Vec
     } else {
         (game.castle_black, '8')
     };
+    if game.is_check(position) {
+        return result;
+    }
     if position == Position::from_chars('e', row).unwrap()
         && castle_h
         && game
@@ -47,6 +36,9 @@ pub fn list_castle_moves(game: &Game, position: Position, player: Player) -> Vec
         && game
             .get_piece_at(Position::from_chars('c', row).unwrap())
             .is_none()
+        && game
+            .get_piece_at(Position::from_chars('b', row).unwrap())
+            .is_none()
         && !game.is_check(Position::from_chars('d', row).unwrap())
     {
         result =
@@ -407,4 +399,28 @@ mod tests {
         let result_positions: HashSet<Position> = result.iter().map(|mv| mv.to).collect();
         assert_that!(result_positions).is_equal_to(expected);
     }
+
+    #[test]
+    fn castle_in_check() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1").unwrap();
+
+        // When
+        let result = list_castle_moves(&game, Position::from("e1").unwrap(), White);
+
+        //Then
+        assert_that!(result.is_empty()).is_true();
+    }
+
+    #[test]
+    fn castle_a_blocked_on_b() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/RN2K3 w Q - 0 1").unwrap();
+
+        // When
+        let result = list_castle_moves(&game, Position::from("e1").unwrap(), White);
+
+        //Then
+        assert_that!(result.is_empty()).is_true();
+    }
 }
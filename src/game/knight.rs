@@ -2,21 +2,7 @@ use board::*;
 use game::*;
 
 pub fn list_knight_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
-    move_list::generate_moves_one_square(
-        game,
-        player,
-        position,
-        &[
-            (1, 2),
-            (2, 1),
-            (1, -2),
-            (2, -1),
-            (-1, -2),
-            (-2, -1),
-            (-1, 2),
-            (-2, 1),
-        ],
-    )
+    move_list::generate_moves(game, Piece::new(PieceType::Knight, player), position)
 }
 
 #[cfg(test)]
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::fmt::Display;
+
+use board::*;
+use game::*;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMoveRule,
+    Repetition,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Outcome {
+    winner: Option<Player>,
+    termination: Termination,
+}
+
+impl Outcome {
+    fn draw(termination: Termination) -> Self {
+        Self {
+            winner: None,
+            termination,
+        }
+    }
+
+    pub fn winner(self) -> Option<Player> {
+        self.winner
+    }
+
+    pub fn termination(self) -> Termination {
+        self.termination
+    }
+
+    pub fn is_draw(self) -> bool {
+        self.winner.is_none()
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.termination {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::FiftyMoveRule => "fifty-move rule",
+            Termination::Repetition => "threefold repetition",
+        };
+        match self.winner {
+            Some(Player::White) => write!(f, "1-0, White wins by {}", reason),
+            Some(Player::Black) => write!(f, "0-1, Black wins by {}", reason),
+            None => write!(f, "1/2-1/2, draw by {}", reason),
+        }
+    }
+}
+
+impl Game {
+    /// Result of the game if it is over, or `None` if the player to move still has to play.
+    /// Draws that could be claimed (fifty-move rule, threefold repetition) are considered claimed.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.list_moves().is_empty() {
+            if self.is_king_check() {
+                Some(Outcome {
+                    winner: Some(self.player_turn.opponent()),
+                    termination: Termination::Checkmate,
+                })
+            } else {
+                Some(Outcome::draw(Termination::Stalemate))
+            }
+        } else if self.is_insufficient_material() {
+            Some(Outcome::draw(Termination::InsufficientMaterial))
+        } else if self.is_fifty_move_draw() {
+            Some(Outcome::draw(Termination::FiftyMoveRule))
+        } else if self.is_threefold_repetition() {
+            Some(Outcome::draw(Termination::Repetition))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use self::outcome::*;
+
+    use board::*;
+    use game::*;
+
+    #[test]
+    fn ongoing() {
+        assert_eq!(Game::new().outcome(), None);
+    }
+
+    #[test]
+    fn checkmate() {
+        // Given
+        let game = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+
+        // When
+        let result = game.outcome().unwrap();
+
+        // Then
+        assert_eq!(result.winner(), Some(Player::White));
+        assert_eq!(result.termination(), Termination::Checkmate);
+        assert_eq!(result.to_string(), "1-0, White wins by checkmate");
+    }
+
+    #[test]
+    fn stalemate() {
+        // Given
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+
+        // When
+        let result = game.outcome().unwrap();
+
+        // Then
+        assert!(result.is_draw());
+        assert_eq!(result.termination(), Termination::Stalemate);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K1n1 w - - 0 1").unwrap();
+
+        // When
+        let result = game.outcome();
+
+        // Then
+        assert_eq!(
+            result.map(Outcome::termination),
+            Some(Termination::InsufficientMaterial)
+        );
+    }
+
+    #[test]
+    fn fifty_move_rule() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 100 80").unwrap();
+
+        // When
+        let result = game.outcome();
+
+        // Then
+        assert_eq!(
+            result.map(Outcome::termination),
+            Some(Termination::FiftyMoveRule)
+        );
+    }
+
+    #[test]
+    fn checkmate_over_fifty_move_rule() {
+        // Given
+        let game = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 100 80").unwrap();
+
+        // When
+        let result = game.outcome();
+
+        // Then
+        assert_eq!(
+            result.map(Outcome::termination),
+            Some(Termination::Checkmate)
+        );
+    }
+
+    #[test]
+    fn repetition() {
+        // Given
+        let game = [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ]
+        .iter()
+        .fold(Game::new(), |game, mv| {
+            game.execute_move(
+                Position::from(&mv[0..2]).unwrap(),
+                Position::from(&mv[2..4]).unwrap(),
+            )
+            .unwrap()
+        });
+
+        // When
+        let result = game.outcome();
+
+        // Then
+        assert_eq!(
+            result.map(Outcome::termination),
+            Some(Termination::Repetition)
+        );
+    }
+}
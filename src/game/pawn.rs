@@ -1,68 +1,84 @@
+use attacks::attacks;
 use board::*;
 use game::*;
 
 pub fn list_pawn_moves(game: &Game, key: Position, player: Player) -> Vector<Move<'_>> {
-    let incr = |i| match player {
-        Player::White => i + 1,
-        Player::Black => i - 1,
+    let board = game.board();
+    let (forward, start_row) = match player {
+        Player::White => (1, b'2'),
+        Player::Black => (-1, b'7'),
     };
+    let mut targets = capture_targets(game, key, player);
+    let simple_move = simple_move(game, key, player);
     let mut jump_position = None;
-    let simple_move = Position::from_u8(key.column(), incr(key.row()));
-    let mut positions = if simple_move.and_then(|sm| game.board().get(sm)).is_none() {
-        let mut advances: Vector<Position> = simple_move.into_iter().collect();
-        if (game.turn() == Player::White && key.row() == b'2')
-            || (game.turn() == Player::Black && key.row() == b'7')
-        {
-            let computed_jump = Position::from_u8(key.column(), incr(incr(key.row()))).unwrap();
-            if game.board().get(computed_jump).is_none() {
-                advances.push_back(computed_jump);
+    if let Some(simple_move) = simple_move {
+        targets |= simple_move.bitboard();
+        if key.row() == start_row {
+            let computed_jump =
+                Position::from_u8(key.column(), (key.row() as i8 + 2 * forward) as u8).unwrap();
+            if board.get(computed_jump).is_none() {
+                targets |= computed_jump.bitboard();
                 jump_position = Some(computed_jump);
-            };
-        };
-        advances
-    } else {
-        vector![]
-    };
-    let captures = vector![
-        Position::from_u8(key.column() - 1, incr(key.row())),
-        Position::from_u8(key.column() + 1, incr(key.row())),
-    ]
-    .into_iter()
-    .flatten()
-    .filter(|pos| {
-        game.board()
-            .get(*pos)
-            .map_or(false, |piece| piece.player() == player.opponent())
-            || game.en_passant == Some(*pos)
-    })
-    .collect();
-    positions.append(captures);
-
-    positions
-        .into_iter()
+            }
+        }
+    }
+
+    positions(targets)
         .flat_map(|position| {
             if Some(position) == jump_position {
-                simple_move.map_or_else(
-                    || vector![],
-                    |sm| vector![game.create_move_en_passant(key, position, sm)],
-                )
-            } else if [b'1', b'8'].contains(&position.row()) {
-                [
-                    PieceType::Queen,
-                    PieceType::Rook,
-                    PieceType::Bishop,
-                    PieceType::Knight,
-                ]
-                .iter()
-                .map(|piece_type| game.create_move_with_promotion(key, position, *piece_type))
-                .collect()
+                vector![game.create_move_en_passant(key, position, simple_move.unwrap())]
             } else {
-                vector![game.create_move(key, position)]
+                create_moves(game, key, position)
             }
         })
         .collect()
 }
 
+/// Captures, including en passant, and promotions.
+pub fn list_pawn_captures(game: &Game, key: Position, player: Player) -> Vector<Move<'_>> {
+    let promotion = simple_move(game, key, player)
+        .filter(|position| is_promotion_row(*position))
+        .map_or(0, Position::bitboard);
+    positions(capture_targets(game, key, player) | promotion)
+        .flat_map(|position| create_moves(game, key, position))
+        .collect()
+}
+
+fn capture_targets(game: &Game, key: Position, player: Player) -> Bitboard {
+    let en_passant = game.en_passant.map_or(0, Position::bitboard);
+    attacks(Piece::new(PieceType::Pawn, player), key, 0)
+        & (game.board().player_occupancy(player.opponent()) | en_passant)
+}
+
+fn simple_move(game: &Game, key: Position, player: Player) -> Option<Position> {
+    let forward = match player {
+        Player::White => 1,
+        Player::Black => -1,
+    };
+    Position::from_u8(key.column(), (key.row() as i8 + forward) as u8)
+        .filter(|position| game.board().get(*position).is_none())
+}
+
+fn is_promotion_row(position: Position) -> bool {
+    [b'1', b'8'].contains(&position.row())
+}
+
+fn create_moves(game: &Game, key: Position, position: Position) -> Vector<Move<'_>> {
+    if is_promotion_row(position) {
+        [
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+        ]
+        .iter()
+        .map(|piece_type| game.create_move_with_promotion(key, position, *piece_type))
+        .collect()
+    } else {
+        vector![game.create_move(key, position)]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use self::pawn::*;
@@ -187,8 +203,8 @@ mod tests {
         let result = list_pawn_moves(&game, Position::from("e7").unwrap(), Player::Black);
 
         // Then
-        assert_that!(result.iter().map(|mv| mv.to).collect::<Vec<Position>>())
-            .does_not_contain(&Position::from("e6").unwrap())
+        let destinations: Vec<Position> = result.iter().map(|mv| mv.to).collect();
+        assert_that!(destinations).does_not_contain(Position::from("e6").unwrap())
     }
 
     #[test]
@@ -211,7 +227,7 @@ mod tests {
         let result = list_pawn_moves(&game, Position::from("e7").unwrap(), Player::Black);
 
         // Then
-        assert_that!(result.iter().map(|mv| mv.to).collect::<Vec<Position>>())
-            .does_not_contain(&Position::from("e5").unwrap())
+        let destinations: Vec<Position> = result.iter().map(|mv| mv.to).collect();
+        assert_that!(destinations).does_not_contain(Position::from("e5").unwrap())
     }
 }
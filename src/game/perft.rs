@@ -0,0 +1,103 @@
+use game::*;
+
+impl Game {
+    /// Number of leaf nodes of the legal move tree at the given depth.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.list_moves();
+        if depth == 1 {
+            moves.len() as u64
+        } else {
+            moves.iter().map(|mv| mv.new_game().perft(depth - 1)).sum()
+        }
+    }
+
+    /// Perft node count below each legal move, to find which move a wrong count comes from.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(OwnedMove, u64)> {
+        self.list_moves()
+            .iter()
+            .map(|mv| {
+                let nodes = if depth > 1 {
+                    mv.new_game().perft(depth - 1)
+                } else {
+                    1
+                };
+                (mv.owned(), nodes)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use game::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_4_MIRRORED: &str =
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+    fn perft(fen: &str, depth: u32) -> u64 {
+        Game::from_fen(fen).unwrap().perft(depth)
+    }
+
+    #[test]
+    fn starting_position() {
+        assert_eq!(perft(STARTING_POSITION, 1), 20);
+        assert_eq!(perft(STARTING_POSITION, 2), 400);
+        assert_eq!(perft(STARTING_POSITION, 3), 8902);
+    }
+
+    #[test]
+    fn kiwipete() {
+        assert_eq!(perft(KIWIPETE, 1), 48);
+        assert_eq!(perft(KIWIPETE, 2), 2039);
+    }
+
+    #[test]
+    fn en_passant_and_pins() {
+        assert_eq!(perft(POSITION_3, 1), 14);
+        assert_eq!(perft(POSITION_3, 2), 191);
+        assert_eq!(perft(POSITION_3, 3), 2812);
+    }
+
+    #[test]
+    fn promotions_and_castling() {
+        assert_eq!(perft(POSITION_4, 1), 6);
+        assert_eq!(perft(POSITION_4, 2), 264);
+        assert_eq!(perft(POSITION_4, 3), 9467);
+        assert_eq!(perft(POSITION_4_MIRRORED, 3), 9467);
+    }
+
+    #[test]
+    fn promotion_with_capture() {
+        assert_eq!(perft(POSITION_5, 1), 44);
+        assert_eq!(perft(POSITION_5, 2), 1486);
+    }
+
+    #[test]
+    fn divide() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = game.perft_divide(2);
+
+        // Then
+        assert_eq!(result.len(), 20);
+        assert!(result.iter().all(|(_, nodes)| *nodes == 20));
+    }
+
+    #[test]
+    #[ignore]
+    fn deep() {
+        assert_eq!(perft(STARTING_POSITION, 4), 197_281);
+        assert_eq!(perft(KIWIPETE, 3), 97_862);
+        assert_eq!(perft(POSITION_3, 4), 43_238);
+        assert_eq!(perft(POSITION_5, 3), 62_379);
+    }
+}
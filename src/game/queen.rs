@@ -2,21 +2,7 @@ use board::*;
 use game::*;
 
 pub fn list_queen_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
-    move_list::generate_moves(
-        game,
-        player,
-        position,
-        &[
-            (0, 1),
-            (1, 0),
-            (0, -1),
-            (-1, 0),
-            (1, 1),
-            (-1, 1),
-            (1, -1),
-            (-1, -1),
-        ],
-    )
+    move_list::generate_moves(game, Piece::new(PieceType::Queen, player), position)
 }
 
 #[cfg(test)]
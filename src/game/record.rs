@@ -0,0 +1,208 @@
+use game::*;
+
+/// A game with the list of moves played from its initial position, which can be navigated back
+/// and forth. Playing a move after going back discards the moves that followed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    positions: Vector<Game>,
+    moves: Vector<OwnedMove>,
+    ply: usize,
+}
+
+impl GameRecord {
+    pub fn new(game: Game) -> Self {
+        Self {
+            positions: vector![game],
+            moves: Vector::new(),
+            ply: 0,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.positions[self.ply]
+    }
+
+    pub fn initial_game(&self) -> &Game {
+        &self.positions[0]
+    }
+
+    /// Number of moves played up to the current position.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Number of moves recorded, including the ones that were undone.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn moves(&self) -> &Vector<OwnedMove> {
+        &self.moves
+    }
+
+    pub fn play(&mut self, mv: OwnedMove) -> Result<(), String> {
+        let new_game = self.game().apply(mv)?;
+        self.positions.truncate(self.ply + 1);
+        self.moves.truncate(self.ply);
+        self.positions.push_back(new_game);
+        self.moves.push_back(mv);
+        self.ply += 1;
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Option<OwnedMove> {
+        if self.ply > 0 {
+            self.ply -= 1;
+            Some(self.moves[self.ply])
+        } else {
+            None
+        }
+    }
+
+    pub fn redo(&mut self) -> Option<OwnedMove> {
+        if self.ply < self.moves.len() {
+            self.ply += 1;
+            Some(self.moves[self.ply - 1])
+        } else {
+            None
+        }
+    }
+
+    pub fn go_to(&mut self, ply: usize) -> Result<(), String> {
+        if ply <= self.moves.len() {
+            self.ply = ply;
+            Ok(())
+        } else {
+            Err(format!("Only {} moves were played", self.moves.len()))
+        }
+    }
+}
+
+impl Default for GameRecord {
+    fn default() -> Self {
+        Self::new(Game::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use game::*;
+
+    fn play(record: &mut GameRecord, uci: &str) {
+        let mv = record.game().parse_uci_move(uci).unwrap().owned();
+        record.play(mv).unwrap();
+    }
+
+    #[test]
+    fn play_moves() {
+        // Given
+        let mut record = GameRecord::default();
+
+        // When
+        play(&mut record, "e2e4");
+        play(&mut record, "e7e5");
+
+        // Then
+        assert_eq!(record.ply(), 2);
+        assert_eq!(record.len(), 2);
+        assert_eq!(
+            record.game().to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+        assert_eq!(record.initial_game(), &Game::new());
+    }
+
+    #[test]
+    fn play_illegal_move() {
+        // Given
+        let mut record = GameRecord::default();
+        play(&mut record, "e2e4");
+        let mv = Game::new().parse_uci_move("d2d4").unwrap().owned();
+
+        // When
+        let result = record.play(mv);
+
+        // Then
+        assert_eq!(result, Err("Illegal move".to_string()));
+        assert_eq!(record.ply(), 1);
+    }
+
+    #[test]
+    fn undo_redo() {
+        // Given
+        let mut record = GameRecord::default();
+        play(&mut record, "e2e4");
+        play(&mut record, "e7e5");
+        let after_e4 = Game::new().parse_uci_move("e2e4").unwrap().new_game();
+
+        // When
+        let undone = record.undo();
+
+        // Then
+        assert_eq!(undone.map(|mv| mv.to_string()), Some("e7e5".to_string()));
+        assert_eq!(record.game(), &after_e4);
+        assert_eq!(record.len(), 2);
+
+        // When
+        let redone = record.redo();
+
+        // Then
+        assert_eq!(redone.map(|mv| mv.to_string()), Some("e7e5".to_string()));
+        assert_eq!(record.ply(), 2);
+        assert_eq!(record.redo(), None);
+    }
+
+    #[test]
+    fn undo_at_start() {
+        // Given
+        let mut record = GameRecord::default();
+
+        // When
+        let result = record.undo();
+
+        // Then
+        assert_eq!(result, None);
+        assert_eq!(record.game(), &Game::new());
+    }
+
+    #[test]
+    fn play_after_undo() {
+        // Given
+        let mut record = GameRecord::default();
+        play(&mut record, "e2e4");
+        play(&mut record, "e7e5");
+        record.undo();
+
+        // When
+        play(&mut record, "c7c5");
+
+        // Then
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.moves()[1].to_string(), "c7c5");
+        assert_eq!(record.redo(), None);
+    }
+
+    #[test]
+    fn go_to() {
+        // Given
+        let mut record = GameRecord::default();
+        for uci in &["g1f3", "g8f6", "f3g1", "f6g8"] {
+            play(&mut record, uci);
+        }
+
+        // When
+        let result = record.go_to(0);
+
+        // Then
+        assert_eq!(result, Ok(()));
+        assert_eq!(record.game(), &Game::new());
+        assert_eq!(record.go_to(4), Ok(()));
+        assert_eq!(record.game().repetition_count(), 2);
+        assert_eq!(record.go_to(5), Err("Only 4 moves were played".to_string()));
+        assert_eq!(record.ply(), 4);
+    }
+}
@@ -2,7 +2,7 @@ use board::*;
 use game::*;
 
 pub fn list_rook_moves(game: &Game, position: Position, player: Player) -> Vector<Move<'_>> {
-    move_list::generate_moves(game, player, position, &[(0, 1), (1, 0), (0, -1), (-1, 0)])
+    move_list::generate_moves(game, Piece::new(PieceType::Rook, player), position)
 }
 
 #[cfg(test)]
@@ -0,0 +1,333 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+
+use regex::Regex;
+
+use board::*;
+use game::*;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum SanError {
+    Syntax(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+
+impl Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::Syntax(san) => write!(f, "Invalid move notation: {}", san),
+            SanError::IllegalMove(san) => write!(f, "Illegal move: {}", san),
+            SanError::AmbiguousMove(san) => write!(f, "Ambiguous move: {}", san),
+        }
+    }
+}
+
+impl Error for SanError {}
+
+impl<'a> Move<'a> {
+    pub fn to_san(&self) -> String {
+        let mv = self.owned();
+        let mut result = if mv.is_castle() {
+            if mv.to().column() == b'g' {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let mut result = String::new();
+            if mv.piece().piece_type() == PieceType::Pawn {
+                if mv.captured().is_some() {
+                    result.push(mv.from().column() as char);
+                }
+            } else {
+                result.push(piece_letter(mv.piece().piece_type()));
+                result.push_str(&self.disambiguation());
+            }
+            if mv.captured().is_some() {
+                result.push('x');
+            }
+            result.push_str(&mv.to().to_string());
+            if let Some(promotion) = mv.promotion() {
+                result.push('=');
+                result.push(piece_letter(promotion));
+            }
+            result
+        };
+        let new_game = self.new_game();
+        if new_game.is_king_check() {
+            if new_game.list_moves().is_empty() {
+                result.push('#');
+            } else {
+                result.push('+');
+            }
+        }
+        if mv.is_en_passant() {
+            result.push_str(" e.p.");
+        }
+        result
+    }
+
+    fn disambiguation(&self) -> String {
+        let piece = self.game.get_piece_at(self.from);
+        let others: Vec<Position> = self
+            .game
+            .list_moves()
+            .iter()
+            .filter(|mv| {
+                mv.to == self.to && mv.from != self.from && self.game.get_piece_at(mv.from) == piece
+            })
+            .map(|mv| mv.from)
+            .collect();
+        if others.is_empty() {
+            String::new()
+        } else if others
+            .iter()
+            .all(|from| from.column() != self.from.column())
+        {
+            (self.from.column() as char).to_string()
+        } else if others.iter().all(|from| from.row() != self.from.row()) {
+            (self.from.row() as char).to_string()
+        } else {
+            self.from.to_string()
+        }
+    }
+}
+
+impl Game {
+    pub fn parse_san(&self, san: &str) -> Result<Move<'_>, SanError> {
+        lazy_static! {
+            static ref SAN_REGEX: Regex = Regex::new(
+                "^(?:(?P<castle>O-O(?:-O)?)|(?P<piece>[KQRBN])?(?P<column>[a-h])?(?P<row>[1-8])?x?(?P<to>[a-h][1-8])(?:=?(?P<promotion>[QRBN]))?)$"
+            )
+            .unwrap();
+        }
+        let normalized = san
+            .trim()
+            .trim_end_matches("e.p.")
+            .trim_end()
+            .trim_end_matches(['+', '#', '!', '?'])
+            .replace('0', "O");
+        let captures = SAN_REGEX
+            .captures(&normalized)
+            .ok_or_else(|| SanError::Syntax(san.to_string()))?;
+        let candidates: Vec<Move> = if let Some(castle) = captures.name("castle") {
+            let column = if castle.as_str() == "O-O" { b'g' } else { b'c' };
+            self.list_moves()
+                .into_iter()
+                .filter(|mv| mv.detect_castle() && mv.to.column() == column)
+                .collect()
+        } else {
+            let piece_type = captures
+                .name("piece")
+                .map_or(PieceType::Pawn, |piece| parse_piece_letter(piece.as_str()));
+            let to = Position::from(&captures["to"]).unwrap();
+            let column = captures.name("column").map(|c| c.as_str().as_bytes()[0]);
+            let row = captures.name("row").map(|r| r.as_str().as_bytes()[0]);
+            let promotion = captures
+                .name("promotion")
+                .map(|promotion| parse_piece_letter(promotion.as_str()));
+            self.list_moves()
+                .into_iter()
+                .filter(|mv| {
+                    mv.to == to
+                        && mv.promotion == promotion
+                        && column.is_none_or(|column| mv.from.column() == column)
+                        && row.is_none_or(|row| mv.from.row() == row)
+                        && self.get_piece_at(mv.from).map(|piece| piece.piece_type())
+                            == Some(piece_type)
+                })
+                .collect()
+        };
+        match candidates.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(SanError::IllegalMove(san.to_string())),
+            _ => Err(SanError::AmbiguousMove(san.to_string())),
+        }
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::King => 'K',
+        PieceType::Queen => 'Q',
+        PieceType::Rook => 'R',
+        PieceType::Bishop => 'B',
+        PieceType::Knight => 'N',
+        PieceType::Pawn => 'P',
+    }
+}
+
+fn parse_piece_letter(letter: &str) -> PieceType {
+    match letter {
+        "K" => PieceType::King,
+        "Q" => PieceType::Queen,
+        "R" => PieceType::Rook,
+        "B" => PieceType::Bishop,
+        "N" => PieceType::Knight,
+        _ => PieceType::Pawn,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use self::san::*;
+
+    use game::*;
+
+    fn san(fen: &str, from: &str, to: &str, promotion: Option<PieceType>) -> String {
+        let game = Game::from_fen(fen).unwrap();
+        let result = game
+            .list_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.from == Position::from(from).unwrap()
+                    && mv.to == Position::from(to).unwrap()
+                    && mv.promotion == promotion
+            })
+            .unwrap()
+            .to_san();
+        result
+    }
+
+    #[test]
+    fn pawn_move() {
+        assert_eq!(san(STARTING_POSITION, "e2", "e4", None), "e4");
+    }
+
+    #[test]
+    fn piece_move() {
+        assert_eq!(san(STARTING_POSITION, "g1", "f3", None), "Nf3");
+    }
+
+    #[test]
+    fn disambiguation_by_file() {
+        let fen = "1n2k3/8/5n2/8/8/8/8/4K3 b - - 0 1";
+        assert_eq!(san(fen, "b8", "d7", None), "Nbd7");
+        assert_eq!(san(fen, "f6", "d7", None), "Nfd7");
+    }
+
+    #[test]
+    fn disambiguation_by_rank() {
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a3", None), "R1a3");
+        assert_eq!(san(fen, "a5", "a3", None), "R5a3");
+    }
+
+    #[test]
+    fn disambiguation_by_square() {
+        let fen = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "b2", None), "Qa1b2");
+        assert_eq!(san(fen, "a3", "b2", None), "Q3b2");
+        assert_eq!(san(fen, "c1", "b2", None), "Qcb2");
+    }
+
+    #[test]
+    fn capture() {
+        let fen = "4k3/8/8/3p4/4P3/8/8/3QK3 w - - 0 1";
+        assert_eq!(san(fen, "e4", "d5", None), "exd5");
+        assert_eq!(san(fen, "d1", "d5", None), "Qxd5");
+    }
+
+    #[test]
+    fn en_passant() {
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert_eq!(san(fen, "e5", "d6", None), "exd6 e.p.");
+    }
+
+    #[test]
+    fn castle() {
+        let fen = "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+        assert_eq!(san(fen, "e1", "c1", None), "O-O-O");
+        assert_eq!(san(fen, "e1", "g1", None), "O-O");
+    }
+
+    #[test]
+    fn promotion_with_check() {
+        let fen = "3k4/4P3/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(san(fen, "e7", "e8", Some(PieceType::Queen)), "e8=Q+");
+        assert_eq!(san(fen, "e7", "e8", Some(PieceType::Knight)), "e8=N");
+    }
+
+    #[test]
+    fn mate() {
+        let fen = "6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a8", None), "Ra8#");
+    }
+
+    #[test]
+    fn parse() {
+        let game = Game::from_fen("1n2k3/8/5n2/8/8/8/8/R3K2R b KQ - 0 1").unwrap();
+        let result = game.parse_san("Nbd7").unwrap();
+        assert_eq!(result.from, Position::from("b8").unwrap());
+        assert_eq!(result.to, Position::from("d7").unwrap());
+        assert_eq!(
+            game.parse_san("Nf6d7").map(|mv| mv.from),
+            Ok(Position::from("f6").unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_castle() {
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(
+            game.parse_san("O-O-O").map(|mv| mv.to),
+            Ok(Position::from("c1").unwrap())
+        );
+        assert_eq!(
+            game.parse_san("0-0").map(|mv| mv.to),
+            Ok(Position::from("g1").unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_suffixes() {
+        let game = Game::from_fen("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.parse_san("e8=Q+").map(|mv| mv.promotion),
+            Ok(Some(PieceType::Queen))
+        );
+        assert_eq!(
+            game.parse_san("e8R!?").map(|mv| mv.promotion),
+            Ok(Some(PieceType::Rook))
+        );
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(
+            game.parse_san("exd6 e.p.")
+                .map(|mv| mv.owned().is_en_passant()),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for mv in game.list_moves() {
+            assert_eq!(game.parse_san(&mv.to_san()), Ok(mv));
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let game = Game::from_fen("1n2k3/8/5n2/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(
+            game.parse_san("Nd7"),
+            Err(SanError::AmbiguousMove("Nd7".to_string()))
+        );
+        assert_eq!(
+            game.parse_san("Nc5"),
+            Err(SanError::IllegalMove("Nc5".to_string()))
+        );
+        assert_eq!(
+            game.parse_san("Zz9"),
+            Err(SanError::Syntax("Zz9".to_string()))
+        );
+        assert_eq!(
+            game.parse_san("O-O"),
+            Err(SanError::IllegalMove("O-O".to_string()))
+        );
+    }
+}
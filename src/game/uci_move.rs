@@ -0,0 +1,155 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+
+use board::*;
+use game::*;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum UciMoveError {
+    Syntax(String),
+    IllegalMove(String),
+}
+
+impl Display for UciMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciMoveError::Syntax(uci) => write!(f, "Invalid UCI move: {}", uci),
+            UciMoveError::IllegalMove(uci) => write!(f, "Illegal move: {}", uci),
+        }
+    }
+}
+
+impl Error for UciMoveError {}
+
+impl Display for OwnedMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        match self.promotion {
+            Some(PieceType::Queen) => write!(f, "q"),
+            Some(PieceType::Rook) => write!(f, "r"),
+            Some(PieceType::Bishop) => write!(f, "b"),
+            Some(PieceType::Knight) => write!(f, "n"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> Display for Move<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.owned().fmt(f)
+    }
+}
+
+impl Game {
+    pub fn parse_uci_move(&self, uci: &str) -> Result<Move<'_>, UciMoveError> {
+        let syntax_error = || UciMoveError::Syntax(uci.to_string());
+        if !uci.is_ascii() || uci.len() < 4 || uci.len() > 5 {
+            return Err(syntax_error());
+        }
+        let from = Position::from(&uci[0..2]).ok_or_else(syntax_error)?;
+        let to = Position::from(&uci[2..4]).ok_or_else(syntax_error)?;
+        let promotion = match &uci[4..] {
+            "" => None,
+            "q" => Some(PieceType::Queen),
+            "r" => Some(PieceType::Rook),
+            "b" => Some(PieceType::Bishop),
+            "n" => Some(PieceType::Knight),
+            _ => return Err(syntax_error()),
+        };
+        self.list_moves()
+            .into_iter()
+            .find(|mv| mv.from == from && mv.to == to && mv.promotion == promotion)
+            .ok_or_else(|| UciMoveError::IllegalMove(uci.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use self::uci_move::*;
+
+    use game::*;
+
+    #[test]
+    fn display() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = game.parse_uci_move("e2e4").unwrap();
+
+        // Then
+        assert_eq!(result.to_string(), "e2e4");
+        assert_eq!(result.owned().to_string(), "e2e4");
+    }
+
+    #[test]
+    fn promotion() {
+        // Given
+        let game = Game::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        // When
+        let result = game.parse_uci_move("a7b8n").unwrap();
+
+        // Then
+        assert_eq!(result.promotion, Some(PieceType::Knight));
+        assert_eq!(result.to_string(), "a7b8n");
+    }
+
+    #[test]
+    fn castle() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+
+        // When
+        let result = game.parse_uci_move("e1c1").unwrap();
+
+        // Then
+        assert!(result.owned().is_castle());
+        assert_eq!(
+            result.new_game().to_fen(),
+            "4k3/8/8/8/8/8/8/2KR3R b - - 1 1"
+        );
+    }
+
+    #[test]
+    fn en_passant() {
+        // Given
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        // When
+        let result = game.parse_uci_move("e5d6").unwrap();
+
+        // Then
+        assert!(result.owned().is_en_passant());
+    }
+
+    #[test]
+    fn round_trip() {
+        let game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for mv in game.list_moves() {
+            assert_eq!(game.parse_uci_move(&mv.to_string()), Ok(mv));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let game = Game::new();
+        assert_eq!(
+            game.parse_uci_move("e2e5"),
+            Err(UciMoveError::IllegalMove("e2e5".to_string()))
+        );
+        assert_eq!(
+            game.parse_uci_move("e7e8q"),
+            Err(UciMoveError::IllegalMove("e7e8q".to_string()))
+        );
+        for uci in &["e2", "e2e4qq", "e2e9", "e2e4k", "é2e4"] {
+            assert_eq!(
+                game.parse_uci_move(uci),
+                Err(UciMoveError::Syntax(uci.to_string()))
+            );
+        }
+    }
+}
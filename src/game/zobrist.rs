@@ -0,0 +1,201 @@
+use board::*;
+use game::*;
+
+struct Keys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castle: [u64; 4],
+    en_passant: [u64; 8],
+}
+
+lazy_static! {
+    static ref KEYS: Keys = {
+        let mut seed: u64 = 0x0123_4567_89ab_cdef;
+        let mut random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let mut pieces = [[[0; 64]; 6]; 2];
+        for key in pieces.iter_mut().flatten().flatten() {
+            *key = random();
+        }
+        let black_to_move = random();
+        let mut castle = [0; 4];
+        for key in castle.iter_mut() {
+            *key = random();
+        }
+        let mut en_passant = [0; 8];
+        for key in en_passant.iter_mut() {
+            *key = random();
+        }
+        Keys {
+            pieces,
+            black_to_move,
+            castle,
+            en_passant,
+        }
+    };
+}
+
+const PIECES: [Piece; 12] = [
+    Piece::new(PieceType::King, Player::White),
+    Piece::new(PieceType::Queen, Player::White),
+    Piece::new(PieceType::Bishop, Player::White),
+    Piece::new(PieceType::Knight, Player::White),
+    Piece::new(PieceType::Rook, Player::White),
+    Piece::new(PieceType::Pawn, Player::White),
+    Piece::new(PieceType::King, Player::Black),
+    Piece::new(PieceType::Queen, Player::Black),
+    Piece::new(PieceType::Bishop, Player::Black),
+    Piece::new(PieceType::Knight, Player::Black),
+    Piece::new(PieceType::Rook, Player::Black),
+    Piece::new(PieceType::Pawn, Player::Black),
+];
+
+impl Game {
+    /// Zobrist key of the position: pieces, side to move, castle rights and en passant square.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn compute_hash(&self) -> u64 {
+        board_hash_change(&Board::empty(), &self.board)
+            ^ self.state_hash()
+            ^ if self.player_turn == Player::Black {
+                KEYS.black_to_move
+            } else {
+                0
+            }
+    }
+
+    pub(super) fn with_computed_hash(self) -> Self {
+        Self {
+            hash: self.compute_hash(),
+            ..self
+        }
+    }
+
+    /// Keys of castle rights and en passant square, which are not updated piece by piece.
+    fn state_hash(&self) -> u64 {
+        let castle = [
+            self.castle_white.0,
+            self.castle_white.1,
+            self.castle_black.0,
+            self.castle_black.1,
+        ];
+        let castle_hash = castle
+            .iter()
+            .zip(KEYS.castle.iter())
+            .filter(|(allowed, _)| **allowed)
+            .fold(0, |hash, (_, key)| hash ^ key);
+        castle_hash
+            ^ self.en_passant.map_or(0, |position| {
+                KEYS.en_passant[(position.column() - b'a') as usize]
+            })
+    }
+
+    /// Key of the game once the given position was reached by playing a move from this game.
+    pub(super) fn next_hash(&self, next: &Game) -> u64 {
+        self.hash
+            ^ board_hash_change(&self.board, &next.board)
+            ^ self.state_hash()
+            ^ next.state_hash()
+            ^ KEYS.black_to_move
+    }
+
+    pub(super) fn toggle_turn_hash(&self) -> u64 {
+        self.hash ^ KEYS.black_to_move
+    }
+}
+
+/// Only the squares that changed between both boards are visited.
+fn board_hash_change(before: &Board, after: &Board) -> u64 {
+    let mut result = 0;
+    for piece in PIECES.iter() {
+        let keys = &KEYS.pieces[piece.player() as usize][piece.piece_type() as usize];
+        for position in positions(before.pieces(*piece) ^ after.pieces(*piece)) {
+            result ^= keys[position.index()];
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use game::*;
+
+    fn check_incremental_hash(game: &Game, depth: u32) {
+        assert_eq!(game.hash(), game.compute_hash(), "{}", game.to_fen());
+        if depth > 0 {
+            for mv in game.list_moves() {
+                check_incremental_hash(&mv.new_game(), depth - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_hash() {
+        for fen in &[
+            STARTING_POSITION,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            check_incremental_hash(&Game::from_fen(fen).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn transposition() {
+        // Given
+        let game = Game::new();
+
+        // When
+        let result = game
+            .parse_san("Nf3")
+            .unwrap()
+            .new_game()
+            .parse_san("Nf6")
+            .unwrap()
+            .new_game()
+            .parse_san("Nc3")
+            .unwrap()
+            .new_game();
+
+        // Then
+        let other = game
+            .parse_san("Nc3")
+            .unwrap()
+            .new_game()
+            .parse_san("Nf6")
+            .unwrap()
+            .new_game()
+            .parse_san("Nf3")
+            .unwrap()
+            .new_game();
+        assert_eq!(result.hash(), other.hash());
+    }
+
+    #[test]
+    fn distinct_positions() {
+        let hash = |fen| Game::from_fen(fen).unwrap().hash();
+        assert_ne!(
+            hash(STARTING_POSITION),
+            hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1")
+        );
+        assert_ne!(
+            hash(STARTING_POSITION),
+            hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1")
+        );
+        assert_ne!(
+            hash("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"),
+            hash("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1")
+        );
+        assert_eq!(
+            Game::new().set_turn(Player::Black).hash(),
+            hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1")
+        );
+    }
+}
@@ -8,6 +8,7 @@ extern crate rayon;
 #[cfg(test)]
 extern crate spectral;
 
+pub mod attacks;
 pub mod board;
 pub mod engine;
 pub mod game;
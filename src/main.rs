@@ -1,48 +1,95 @@
 mod uci;
+mod uci_engine;
 mod worker;
 
-extern crate im;
 extern crate woodpusher;
 
+use std::env;
 use std::io;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
-use im::Vector;
-
-use woodpusher::board::Position;
-use woodpusher::engine;
+use woodpusher::engine::moves_to_mate;
+use woodpusher::engine::Engine;
+use woodpusher::engine::SearchInfo;
+use woodpusher::engine::TimeControl;
 use woodpusher::game::Game;
+use woodpusher::game::OwnedMove;
 use woodpusher::game_cli;
 
+const CLOCK: Duration = Duration::from_secs(60);
+const INCREMENT: Duration = Duration::from_secs(1);
+
+/// Speaks UCI when started with the `uci` argument. Otherwise the engine plays against itself.
 fn main() -> io::Result<()> {
+    let mut engine = Engine::new();
+    engine.set_threads(thread::available_parallelism().map_or(1, |threads| threads.get()));
+    if env::args().nth(1).as_deref() == Some("uci") {
+        return uci_engine::run(engine);
+    }
     let mut game = Game::new();
-    let mut score = 0;
-    while !game.list_moves().is_empty() {
+    engine.set_progress(|info| println!("{}", format_info(info)));
+    let mut clocks = [CLOCK, CLOCK];
+    let mut score = String::new();
+    let mut pv = Vec::new();
+    let outcome = loop {
+        if let Some(outcome) = game.outcome() {
+            break outcome;
+        }
         println!("{}", game_cli::show_board(game.board()));
         println!("Score: {}", score);
+        println!("PV: {}", format_pv(&pv));
         game = {
-            let (m, s) = engine::select_move(&game, 3);
-            score = s;
-            m.map_or(game.clone(), |mv| mv.new_game())
+            let clock = &mut clocks[game.turn() as usize];
+            let start = Instant::now();
+            let result =
+                engine.select_move_with_clock(&game, &TimeControl::new(*clock, INCREMENT, None));
+            *clock = clock.saturating_sub(start.elapsed()) + INCREMENT;
+            score = format_score(result.score());
+            pv = result.pv().to_vec();
+            result.best_move().map_or(game.clone(), |mv| {
+                println!("{}. {}", game.fullmove_number(), mv.to_san());
+                mv.new_game()
+            })
         };
-    }
+    };
+    println!("{}", game_cli::show_board(game.board()));
+    println!("{}", outcome);
     Ok(())
 }
 
-fn ask_position() -> io::Result<(Position, Position)> {
-    let mut result = None;
-    while result.is_none() {
-        println!("Enter start and destination positions");
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let input_list: Vector<&str> = input.trim().split(' ').collect();
-        if input_list.len() == 2 {
-            let tuple_result = (Position::from(input_list[0]), Position::from(input_list[1]));
-            result = match tuple_result {
-                (Some(_), Some(_)) => Some(tuple_result),
-                _ => None,
-            }
-        }
+/// Score of the last move from the point of view of the player who played it.
+fn format_score(score: i32) -> String {
+    match moves_to_mate(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => score.to_string(),
     }
-    let (from, to) = result.unwrap();
-    Ok((from.unwrap(), to.unwrap()))
+}
+
+fn format_info(info: &SearchInfo) -> String {
+    let mut line = format!(
+        "Depth {}/{}, {} nodes, {} nps, {:.1}s, hash {}‰",
+        info.depth(),
+        info.seldepth(),
+        info.nodes(),
+        info.nps(),
+        info.elapsed().as_secs_f64(),
+        info.hashfull()
+    );
+    if let Some(score) = info.score() {
+        line += &format!(
+            ", score {}, PV: {}",
+            format_score(score),
+            format_pv(info.pv())
+        );
+    }
+    line
+}
+
+fn format_pv(pv: &[OwnedMove]) -> String {
+    pv.iter()
+        .map(OwnedMove::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -1,60 +1,185 @@
-use std::sync::mpsc;
-use std::sync::mpsc::{channel, Sender, TryRecvError};
+use std::sync::mpsc::{channel, Sender};
 use std::time::Duration;
 
+use woodpusher::board::Player;
+use woodpusher::engine::moves_to_mate;
+use woodpusher::engine::SearchInfo;
+use woodpusher::engine::SearchLimits;
+use woodpusher::engine::TimeControl;
+use woodpusher::game::Game;
 use worker::Command;
 
-pub fn uci_command(input: &str, sender: &Sender<Command>) -> &'static str {
-    match input {
-        "uci" => uci_result,
+pub fn uci_command(input: &str, sender: &Sender<Command>) -> String {
+    let result = match input {
+        "uci" => UCI_RESULT,
         "isready" => isready(sender),
-        "position startpos" => startpos(sender),
-        "go" => go(sender),
+        "ucinewgame" => new_game(sender),
+        _ if input == "position" || input.starts_with("position ") => {
+            return position(input, sender)
+        }
+        _ if input == "go" || input.starts_with("go ") => go(input, sender),
         "stop" => stop(sender),
         "quit" => quit(sender),
         _ => "",
-    }
+    };
+    result.to_string()
 }
 
-const uci_result: &str = "id name woodpusher\n\
+const UCI_RESULT: &str = "id name woodpusher\n\
     id author Sébastien Willmann\n\
     uciok";
 
-const isready_result: &str = "readyok";
+const ISREADY_RESULT: &str = "readyok";
 
 fn isready(sender: &Sender<Command>) -> &'static str {
     let (response_sender, response_receiver) = channel();
-    sender.send(Command::Ready(response_sender));
+    sender
+        .send(Command::Ready(response_sender))
+        .expect("Engine thread stopped");
     response_receiver
         .recv_timeout(Duration::from_secs(60))
         .expect("Engine unresponsive");
-    isready_result
+    ISREADY_RESULT
 }
 
-fn startpos(sender: &Sender<Command>) -> &'static str {
-    sender.send(Command::StartPos);
+fn new_game(sender: &Sender<Command>) -> &'static str {
+    sender
+        .send(Command::NewGame)
+        .expect("Engine thread stopped");
     ""
 }
 
-fn go(sender: &Sender<Command>) -> &'static str {
-    sender.send(Command::Go);
+/// Sets up the position, or reports why it is invalid and keeps the previous one.
+fn position(input: &str, sender: &Sender<Command>) -> String {
+    match parse_position(input) {
+        Ok(game) => {
+            sender
+                .send(Command::Position(game))
+                .expect("Engine thread stopped");
+            String::new()
+        }
+        Err(error) => format!("info string {}", error),
+    }
+}
+
+/// Reads `position [startpos | fen <6 fields>] [moves <move>...]`.
+fn parse_position(input: &str) -> Result<Game, String> {
+    let mut tokens = input.split_whitespace().skip(1).peekable();
+    let mut game = match tokens.next() {
+        Some("startpos") => Game::new(),
+        Some("fen") => {
+            let mut fields = Vec::new();
+            while let Some(field) = tokens.next_if(|token| *token != "moves") {
+                fields.push(field);
+            }
+            Game::from_fen(&fields.join(" ")).map_err(|error| error.to_string())?
+        }
+        _ => return Err(String::from("Expected startpos or fen")),
+    };
+    match tokens.next() {
+        Some("moves") | None => {}
+        Some(token) => return Err(format!("Expected moves, got {}", token)),
+    }
+    for uci in tokens {
+        game = game
+            .parse_uci_move(uci)
+            .map_err(|error| error.to_string())?
+            .new_game();
+    }
+    Ok(game)
+}
+
+fn go(input: &str, sender: &Sender<Command>) -> &'static str {
+    sender.send(parse_go(input)).expect("Engine thread stopped");
     ""
 }
 
+/// Reads the parameters of the go command. The unknown ones are ignored.
+fn parse_go(input: &str) -> Command {
+    let mut limits = SearchLimits::new();
+    let mut times = [None; 2];
+    let mut increments = [Duration::ZERO; 2];
+    let mut moves_to_go = None;
+    let mut tokens = input.split_whitespace().skip(1);
+    while let Some(token) = tokens.next() {
+        // Clocks may be negative when the time is up
+        let mut value = || {
+            tokens
+                .next()
+                .and_then(|value| value.parse::<i64>().ok())
+                .map(|value| value.max(0) as u64)
+        };
+        let millis = Duration::from_millis;
+        match token {
+            "infinite" => limits = SearchLimits::infinite(),
+            "depth" => limits = value().map_or(limits, |depth| limits.with_depth(depth as i32)),
+            "nodes" => limits = value().map_or(limits, |nodes| limits.with_nodes(nodes)),
+            "movetime" => {
+                limits = value().map_or(limits, |time| limits.with_movetime(millis(time)))
+            }
+            "mate" => limits = value().map_or(limits, |moves| limits.with_mate(moves as i32)),
+            "wtime" => times[Player::White as usize] = value().map(millis),
+            "btime" => times[Player::Black as usize] = value().map(millis),
+            "winc" => increments[Player::White as usize] = value().map_or(Duration::ZERO, millis),
+            "binc" => increments[Player::Black as usize] = value().map_or(Duration::ZERO, millis),
+            "movestogo" => moves_to_go = value().map(|moves| moves as u32),
+            _ => {}
+        }
+    }
+    let clock = |player: Player| {
+        times[player as usize]
+            .map(|time| TimeControl::new(time, increments[player as usize], moves_to_go))
+    };
+    Command::Go(limits, [clock(Player::White), clock(Player::Black)])
+}
+
 fn stop(sender: &Sender<Command>) -> &'static str {
-    sender.send(Command::Stop);
+    sender.send(Command::Stop).expect("Engine thread stopped");
     ""
 }
 
 fn quit(sender: &Sender<Command>) -> &'static str {
-    sender.send(Command::Quit);
+    sender.send(Command::Quit).expect("Engine thread stopped");
     ""
 }
 
+/// Info line of the search progress, with the score and PV once a depth is completed.
+pub fn info(info: &SearchInfo) -> String {
+    let mut line = format!(
+        "info depth {} seldepth {} nodes {} nps {} time {} hashfull {}",
+        info.depth(),
+        info.seldepth(),
+        info.nodes(),
+        info.nps(),
+        info.elapsed().as_millis(),
+        info.hashfull()
+    );
+    if let Some(score) = info.score() {
+        match moves_to_mate(score) {
+            Some(moves) => line += &format!(" score mate {}", moves),
+            None => line += &format!(" score cp {}", score),
+        }
+    }
+    if !info.pv().is_empty() {
+        line += " pv";
+        for mv in info.pv() {
+            line += &format!(" {}", mv);
+        }
+    }
+    line
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::mpsc;
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::{Arc, Mutex};
     use std::thread;
     use uci::*;
+    use woodpusher::engine::Engine;
+    use woodpusher::engine::SearchLimits;
+    use woodpusher::engine::TimeControl;
+    use woodpusher::game::Game;
     use worker::Command;
 
     #[test]
@@ -87,7 +212,7 @@ mod tests {
         // When
         let result = thread::spawn(move || {
             let r = uci_command("isready", &thread_sender);
-            thread_test_sender.send(());
+            thread_test_sender.send(()).unwrap();
             r
         });
 
@@ -96,7 +221,7 @@ mod tests {
 
         // When
         match receiver.recv_timeout(Duration::from_secs(1)) {
-            Ok(Command::Ready(response_sender)) => response_sender.send(()),
+            Ok(Command::Ready(response_sender)) => response_sender.send(()).unwrap(),
             x => panic!("Expected ready command, got {:?}", x),
         };
 
@@ -105,6 +230,22 @@ mod tests {
         assert_eq!(result.join().unwrap(), "readyok");
     }
 
+    #[test]
+    fn ucinewgame_command() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        let result = uci_command("ucinewgame", &sender);
+
+        // Then
+        assert_eq!(result, "");
+        match receiver.try_recv() {
+            Ok(Command::NewGame) => Ok(()),
+            x => Err(format!("Expected command ucinewgame, got {:?}", x)),
+        }
+    }
+
     #[test]
     fn position_startpos_command() -> Result<(), String> {
         // Given
@@ -116,8 +257,79 @@ mod tests {
         // Then
         assert_eq!(result, "");
         match receiver.try_recv() {
-            Ok(Command::StartPos) => Ok(()),
-            _ => Err(String::from("Expected command startpos")),
+            Ok(Command::Position(game)) if game.to_fen() == Game::new().to_fen() => Ok(()),
+            x => Err(format!("Expected command position startpos, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn position_startpos_with_moves_command() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command("position startpos moves g1f3 g8f6 f3g1 f6g8 e2e4", &sender);
+
+        // Then
+        let expected = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3";
+        match receiver.try_recv() {
+            Ok(Command::Position(game)) if game.to_fen() == expected => Ok(()),
+            x => Err(format!("Expected command position with moves, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn position_keeps_repetitions() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command("position startpos moves g1f3 g8f6 f3g1 f6g8", &sender);
+
+        // Then
+        match receiver.try_recv() {
+            Ok(Command::Position(game)) if game.repetition_count() == 2 => Ok(()),
+            x => Err(format!("Expected a repeated position, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn position_fen_command() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command(
+            "position fen 4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1 moves e5d6 e8d7",
+            &sender,
+        );
+
+        // Then
+        let expected = "8/3k4/3P4/8/8/8/8/4K3 w - - 1 2";
+        match receiver.try_recv() {
+            Ok(Command::Position(game)) if game.to_fen() == expected => Ok(()),
+            x => Err(format!("Expected command position fen, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn invalid_position_command() {
+        for input in &[
+            "position",
+            "position fen 4k3/8/8/8/8/8/8/4K3 w - - 0",
+            "position fen 4k3/8/8/8/8/8/8/4K3 w - - 0 1 e1e2",
+            "position startpos moves e2e4 e2e4",
+            "position startpos moves e2",
+        ] {
+            // Given
+            let (sender, receiver) = mpsc::channel();
+
+            // When
+            let result = uci_command(input, &sender);
+
+            // Then
+            assert!(result.starts_with("info string "), "{}", input);
+            assert!(receiver.try_recv().is_err(), "{}", input);
         }
     }
 
@@ -132,11 +344,73 @@ mod tests {
         // Then
         assert_eq!(result, "");
         match receiver.try_recv() {
-            Ok(Command::Go) => Ok(()),
+            Ok(Command::Go(limits, [None, None])) if limits == SearchLimits::new() => Ok(()),
             _ => Err(String::from("Expected command go")),
         }
     }
 
+    #[test]
+    fn go_command_with_clocks() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command(
+            "go wtime 60000 btime 30000 winc 1000 binc 0 movestogo 20",
+            &sender,
+        );
+
+        // Then
+        let white = TimeControl::new(Duration::from_secs(60), Duration::from_secs(1), Some(20));
+        let black = TimeControl::new(Duration::from_secs(30), Duration::from_secs(0), Some(20));
+        match receiver.try_recv() {
+            Ok(Command::Go(limits, clocks))
+                if limits == SearchLimits::new() && clocks == [Some(white), Some(black)] =>
+            {
+                Ok(())
+            }
+            x => Err(format!("Expected command go with clocks, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn go_command_with_limits() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command(
+            "go depth 5 nodes 1000 movetime 200 mate 3 searchmoves e2e4",
+            &sender,
+        );
+
+        // Then
+        let expected = SearchLimits::new()
+            .with_depth(5)
+            .with_nodes(1000)
+            .with_movetime(Duration::from_millis(200))
+            .with_mate(3);
+        match receiver.try_recv() {
+            Ok(Command::Go(limits, [None, None])) if limits == expected => Ok(()),
+            x => Err(format!("Expected command go with limits, got {:?}", x)),
+        }
+    }
+
+    #[test]
+    fn go_infinite_command() -> Result<(), String> {
+        // Given
+        let (sender, receiver) = mpsc::channel();
+
+        // When
+        uci_command("go infinite", &sender);
+
+        // Then
+        match receiver.try_recv() {
+            Ok(Command::Go(limits, _)) if limits.is_infinite() => Ok(()),
+            x => Err(format!("Expected command go infinite, got {:?}", x)),
+        }
+    }
+
     #[test]
     fn stop_command() -> Result<(), String> {
         // Given
@@ -168,4 +442,26 @@ mod tests {
             _ => Err(String::from("Expected command quit")),
         }
     }
+
+    #[test]
+    fn info_line() {
+        // Given
+        let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut engine = Engine::with_hash_size(1);
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let progress_infos = Arc::clone(&infos);
+        engine.set_progress(move |search_info| {
+            progress_infos.lock().unwrap().push(info(search_info))
+        });
+
+        // When
+        engine.select_move(&game, 4);
+
+        // Then
+        let infos = infos.lock().unwrap();
+        let last = infos.last().unwrap();
+        assert!(last.starts_with("info depth 4 seldepth "));
+        assert!(last.contains(" score mate 2 pv "));
+        assert!(last.ends_with(" a8b8 h1h8"));
+    }
 }
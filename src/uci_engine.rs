@@ -1,5 +1,36 @@
+use std::io;
+use std::io::BufRead;
 use std::sync::mpsc::channel;
+use std::thread;
 
-pub fn run() -> Unit {
-    let (sender, receiver) = channel();
+use uci;
+use woodpusher::engine::Engine;
+use worker;
+
+/// Reads the UCI commands from the standard input until quit, while the engine searches on its
+/// own thread. The search progress and the best moves are printed as soon as they are known.
+pub fn run(engine: Engine) -> io::Result<()> {
+    let (sender, commands) = channel();
+    let (output, lines) = channel();
+    let worker = thread::spawn(move || worker::run(engine, commands, output));
+    let printer = thread::spawn(move || {
+        for line in lines {
+            println!("{}", line);
+        }
+    });
+    for input in io::stdin().lock().lines() {
+        let input = input?;
+        let result = uci::uci_command(input.trim(), &sender);
+        if !result.is_empty() {
+            println!("{}", result);
+        }
+        if input.trim() == "quit" {
+            break;
+        }
+    }
+    // The end of the input quits as well
+    drop(sender);
+    worker.join().expect("Engine thread panicked");
+    printer.join().expect("Output thread panicked");
+    Ok(())
 }
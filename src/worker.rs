@@ -1,10 +1,325 @@
-use std::sync::mpsc::Sender;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::thread::JoinHandle;
+
+use uci;
+use woodpusher::engine::Engine;
+use woodpusher::engine::SearchLimits;
+use woodpusher::engine::StopHandle;
+use woodpusher::engine::TimeControl;
+use woodpusher::game::Game;
 
 #[derive(Debug, Clone)]
 pub enum Command {
     Ready(Sender<()>),
-    StartPos,
-    Go,
+    /// Forgets the previous games and sets up the initial position.
+    NewGame,
+    Position(Game),
+    /// Limits of the search, and the clock of each player if any.
+    Go(SearchLimits, [Option<TimeControl>; 2]),
     Stop,
     Quit,
 }
+
+/// A search running on its own thread, which gives the engine back when done.
+struct Search {
+    stop: StopHandle,
+    /// Lets an infinite search send its best move.
+    stopped: Sender<()>,
+    thread: JoinHandle<Box<Engine>>,
+}
+
+enum State {
+    Idle(Box<Engine>),
+    Searching(Search),
+}
+
+impl State {
+    /// Stops the search if any, and waits for the engine.
+    fn stop(self) -> Box<Engine> {
+        match self {
+            State::Idle(engine) => engine,
+            State::Searching(search) => {
+                search.stop.stop();
+                // The search may be over already, and waiting for the stop
+                let _ = search.stopped.send(());
+                search.thread.join().expect("Search thread panicked")
+            }
+        }
+    }
+}
+
+/// Runs the commands until quit or the end of the commands, and sends the search progress and the
+/// best moves to the output.
+pub fn run(mut engine: Engine, commands: Receiver<Command>, output: Sender<String>) {
+    let progress_output = output.clone();
+    engine.set_progress(move |info| {
+        // The UCI thread may have quit while the search was stopping
+        let _ = progress_output.send(uci::info(info));
+    });
+    let mut game = Game::new();
+    let mut state = State::Idle(Box::new(engine));
+    for command in commands.iter() {
+        state = match command {
+            Command::Ready(response) => {
+                response.send(()).expect("UCI thread stopped");
+                state
+            }
+            Command::NewGame => {
+                let mut engine = state.stop();
+                engine.new_game();
+                game = Game::new();
+                State::Idle(engine)
+            }
+            Command::Position(position) => {
+                game = position;
+                state
+            }
+            Command::Go(limits, clocks) => {
+                // The clock is only used when there is no other time limit
+                let limits = match clocks[game.turn() as usize] {
+                    Some(clock) if limits.movetime().is_none() => limits.with_time_control(&clock),
+                    _ => limits,
+                };
+                State::Searching(go(state.stop(), game.clone(), limits, output.clone()))
+            }
+            Command::Stop => State::Idle(state.stop()),
+            Command::Quit => break,
+        }
+    }
+    state.stop();
+}
+
+/// Searches until a limit is reached or the search is stopped, then sends the best move. An
+/// infinite search only sends it once stopped, even if it completed all the depths.
+fn go(mut engine: Box<Engine>, game: Game, limits: SearchLimits, output: Sender<String>) -> Search {
+    let stop = engine.stop_handle();
+    let (stopped, stopped_receiver) = channel();
+    let thread = thread::spawn(move || {
+        let best_move = engine
+            .search(&game, &limits)
+            .best_move()
+            .map_or(String::from("0000"), |mv| mv.owned().to_string());
+        if limits.is_infinite() {
+            // Also returns when the worker is gone
+            let _ = stopped_receiver.recv();
+        }
+        output
+            .send(format!("bestmove {}", best_move))
+            .expect("UCI thread stopped");
+        engine
+    });
+    Search {
+        stop,
+        stopped,
+        thread,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use woodpusher::engine::Engine;
+    use woodpusher::engine::SearchLimits;
+    use woodpusher::engine::TimeControl;
+    use woodpusher::game::Game;
+    use worker::*;
+
+    fn go_infinite() -> Command {
+        Command::Go(SearchLimits::infinite(), [None, None])
+    }
+
+    fn go_depth(depth: i32) -> Command {
+        Command::Go(SearchLimits::new().with_depth(depth), [None, None])
+    }
+
+    /// Lines sent until the best move, included.
+    fn lines_until_best_move(receiver: &Receiver<String>) -> Vec<String> {
+        let mut lines = Vec::new();
+        while lines
+            .last()
+            .is_none_or(|line: &String| !line.starts_with("bestmove "))
+        {
+            lines.push(receiver.recv_timeout(Duration::from_secs(10)).unwrap());
+        }
+        lines
+    }
+
+    fn start() -> (Sender<Command>, Receiver<String>, thread::JoinHandle<()>) {
+        let (sender, commands) = mpsc::channel();
+        let (output, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || run(Engine::with_hash_size(1), commands, output));
+        (sender, receiver, worker)
+    }
+
+    #[test]
+    fn go_then_stop() {
+        // Given
+        let (sender, receiver, worker) = start();
+        sender.send(Command::Position(Game::new())).unwrap();
+        sender.send(go_infinite()).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        // When
+        sender.send(Command::Stop).unwrap();
+
+        // Then
+        let result = lines_until_best_move(&receiver).pop().unwrap();
+        assert_ne!(result, "bestmove 0000");
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn ready_while_searching() {
+        // Given
+        let (sender, receiver, worker) = start();
+        sender.send(go_infinite()).unwrap();
+
+        // When
+        let (response_sender, response_receiver) = mpsc::channel();
+        sender.send(Command::Ready(response_sender)).unwrap();
+
+        // Then
+        assert_eq!(
+            response_receiver.recv_timeout(Duration::from_secs(1)),
+            Ok(())
+        );
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+        assert!(receiver.try_iter().last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn info_while_searching() {
+        // Given
+        let (sender, receiver, worker) = start();
+
+        // When
+        sender.send(go_infinite()).unwrap();
+
+        // Then
+        let result = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(result.starts_with("info depth 1 "));
+        assert!(result.contains(" score cp "));
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn infinite_search_waits_for_stop() {
+        // Given
+        let game = Game::from_fen("k7/8/1QK5/8/8/8/8/8 b - - 0 1").unwrap();
+        let (output, receiver) = mpsc::channel();
+        let search = go(
+            Box::new(Engine::with_hash_size(1)),
+            game,
+            SearchLimits::infinite(),
+            output,
+        );
+        thread::sleep(Duration::from_millis(100));
+        assert!(receiver.try_recv().is_err());
+
+        // When
+        State::Searching(search).stop();
+
+        // Then
+        assert_eq!(receiver.try_recv(), Ok(String::from("bestmove 0000")));
+    }
+
+    #[test]
+    fn go_from_position() {
+        // Given
+        let (sender, receiver, worker) = start();
+        let game = Game::from_fen("k7/8/1QK5/8/8/8/8/8 b - - 0 1").unwrap();
+
+        // When
+        sender.send(Command::Position(game)).unwrap();
+        sender.send(go_depth(2)).unwrap();
+
+        // Then
+        let result = lines_until_best_move(&receiver).pop().unwrap();
+        assert_eq!(result, "bestmove 0000");
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn new_game_while_searching() {
+        // Given
+        let (sender, receiver, worker) = start();
+        let game = Game::from_fen("k7/8/1QK5/8/8/8/8/8 b - - 0 1").unwrap();
+        sender.send(Command::Position(game)).unwrap();
+        sender.send(go_infinite()).unwrap();
+
+        // When
+        sender.send(Command::NewGame).unwrap();
+        sender.send(go_depth(1)).unwrap();
+
+        // Then
+        assert_eq!(
+            lines_until_best_move(&receiver).pop().unwrap(),
+            "bestmove 0000"
+        );
+        assert_ne!(
+            lines_until_best_move(&receiver).pop().unwrap(),
+            "bestmove 0000"
+        );
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn stop_after_search_finished() {
+        // Given
+        let (sender, receiver, worker) = start();
+        sender.send(go_depth(2)).unwrap();
+        lines_until_best_move(&receiver);
+        sender.send(Command::Stop).unwrap();
+
+        // When
+        sender.send(go_depth(3)).unwrap();
+
+        // Then
+        let lines = lines_until_best_move(&receiver);
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("info depth 3 ") && line.contains(" pv ")));
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn go_with_clock() {
+        // Given
+        let (sender, receiver, worker) = start();
+        let clock = TimeControl::new(Duration::from_secs(1), Duration::from_secs(0), Some(10));
+
+        // When
+        sender
+            .send(Command::Go(SearchLimits::new(), [Some(clock), None]))
+            .unwrap();
+
+        // Then
+        let result = lines_until_best_move(&receiver).pop().unwrap();
+        assert_ne!(result, "bestmove 0000");
+        sender.send(Command::Quit).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn quit_when_idle() {
+        // Given
+        let (sender, receiver, worker) = start();
+
+        // When
+        sender.send(Command::Quit).unwrap();
+
+        // Then
+        worker.join().unwrap();
+        assert!(receiver.try_recv().is_err());
+    }
+}